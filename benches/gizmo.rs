@@ -0,0 +1,198 @@
+//! Measures the per-frame cost of `Gizmo::interact` with simulated input.
+//!
+//! Each case runs a headless egui frame containing 1, 10 or 100 gizmos laid out in
+//! a grid, either with the pointer hovering the center of the screen or without
+//! a pointer at all. The frame time is measured with criterion. The number of heap
+//! allocations per frame is counted separately and printed with each case, so
+//! regressions in either are visible between runs.
+//!
+//! With the `bench` feature, the accesses of egui memory per frame are printed as well,
+//! next to the accesses of subgizmo states, each of which accessed egui memory before
+//! the gizmo state was loaded once per frame.
+//!
+//! Run with `cargo bench --bench gizmo`, or `cargo bench --bench gizmo --features bench`.
+//! With the pinned toolchain, the dependencies of criterion are first pinned to versions
+//! supporting it, like in CI: `cargo update -p clap --precise 4.4.18` and
+//! `cargo update -p half --precise 2.4.1`.
+
+#![allow(clippy::print_stdout, unsafe_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use egui::{CentralPanel, Context, Event, Frame, Pos2, RawInput, Rect, Vec2};
+use egui_gizmo::{Gizmo, GizmoMode};
+use glam::{Mat4, Vec3};
+
+/// Number of frames run before measuring, so that egui memory and fonts are initialized
+const WARMUP_FRAMES: usize = 20;
+/// Number of frames over which the allocations and memory accesses are counted
+const COUNTED_FRAMES: usize = 100;
+
+const SCREEN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+/// Distance between neighbouring gizmos in world units
+const GRID_SPACING: f32 = 2.0;
+
+/// Counts the allocations made by the benchmark process
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Accesses of egui memory and of subgizmo states so far, if they are counted
+fn accesses() -> (Option<usize>, Option<usize>) {
+    #[cfg(feature = "bench")]
+    return (
+        Some(egui_gizmo::bench::memory_accesses()),
+        Some(egui_gizmo::bench::state_accesses()),
+    );
+    #[cfg(not(feature = "bench"))]
+    (None, None)
+}
+
+struct Scene {
+    ctx: Context,
+    view: Mat4,
+    projection: Mat4,
+    models: Vec<Mat4>,
+    time: f64,
+}
+
+impl Scene {
+    /// Scene with `count` gizmos laid out in a square grid in front of the camera
+    fn new(count: usize) -> Self {
+        let columns = (count as f32).sqrt().ceil() as usize;
+        let offset = (columns - 1) as f32 * GRID_SPACING * 0.5;
+        let models = (0..count)
+            .map(|i| {
+                let x = (i % columns) as f32 * GRID_SPACING - offset;
+                let y = (i / columns) as f32 * GRID_SPACING - offset;
+                Mat4::from_translation(Vec3::new(x, y, 0.0))
+            })
+            .collect();
+
+        let distance = 10.0 + columns as f32 * GRID_SPACING;
+
+        Self {
+            ctx: Context::default(),
+            view: Mat4::look_at_rh(Vec3::new(0.0, 0.0, distance), Vec3::ZERO, Vec3::Y),
+            projection: Mat4::perspective_rh(
+                45_f32.to_radians(),
+                SCREEN_SIZE.x / SCREEN_SIZE.y,
+                0.1,
+                1000.0,
+            ),
+            models,
+            time: 0.0,
+        }
+    }
+
+    /// Runs a single frame where every gizmo of the scene interacts with the input
+    fn frame(&mut self, mode: GizmoMode, pointer: Option<Pos2>) {
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, SCREEN_SIZE)),
+            time: Some(self.time),
+            events: pointer.map(Event::PointerMoved).into_iter().collect(),
+            ..Default::default()
+        };
+        self.time += 1.0 / 60.0;
+
+        let output = self.ctx.run(input, |ctx| {
+            CentralPanel::default()
+                .frame(Frame::none())
+                .show(ctx, |ui| {
+                    for (i, model) in self.models.iter().enumerate() {
+                        let result = Gizmo::new(("gizmo", i))
+                            .view_matrix(self.view.to_cols_array_2d())
+                            .projection_matrix(self.projection.to_cols_array_2d())
+                            .model_matrix(model.to_cols_array_2d())
+                            .mode(mode)
+                            .interact(ui);
+                        black_box(result);
+                    }
+                });
+        });
+        black_box(output);
+    }
+
+    /// Prints the allocations and memory accesses per frame, which criterion does not measure
+    fn print_counts(&mut self, name: &str, mode: GizmoMode, pointer: Option<Pos2>) {
+        let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+        let (memory_before, state_before) = accesses();
+        for _ in 0..COUNTED_FRAMES {
+            self.frame(mode, pointer);
+        }
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+        let (memory_after, state_after) = accesses();
+
+        let per_frame = |before: Option<usize>, after: Option<usize>| {
+            before.zip(after).map_or_else(
+                || "-".to_owned(),
+                |(before, after)| ((after - before) / COUNTED_FRAMES).to_string(),
+            )
+        };
+        println!(
+            "{name}: {} allocs/frame, {} memory/frame, {} states/frame",
+            allocations / COUNTED_FRAMES,
+            per_frame(memory_before, memory_after),
+            per_frame(state_before, state_after)
+        );
+    }
+}
+
+fn interact(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interact");
+
+    for mode in [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale] {
+        for count in [1, 10, 100] {
+            for hovered in [false, true] {
+                let mode_name = format!("{mode:?}").to_lowercase();
+                let case = format!("{count}/{}", if hovered { "hovered" } else { "idle" });
+                let pointer = hovered.then(|| Pos2::new(SCREEN_SIZE.x, SCREEN_SIZE.y) * 0.5);
+
+                let name = format!("interact/{mode_name}/{case}");
+
+                // The scene is set up on the first run of the routine, which is skipped
+                // for the cases filtered out on the command line
+                let mut scene = None;
+                group.bench_function(BenchmarkId::new(mode_name, case), |b| {
+                    let scene = scene.get_or_insert_with(|| {
+                        let mut scene = Scene::new(count);
+                        for _ in 0..WARMUP_FRAMES {
+                            scene.frame(mode, pointer);
+                        }
+                        scene.print_counts(&name, mode, pointer);
+                        scene
+                    });
+                    b.iter(|| scene.frame(mode, pointer));
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, interact);
+criterion_main!(benches);
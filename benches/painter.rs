@@ -0,0 +1,200 @@
+//! Compares the filled primitives of the painter with drawing the same shapes
+//! from line segments, the way filled shapes were drawn before the painter had them.
+//!
+//! Each case adds a number of shapes to a headless egui frame and tessellates its
+//! output, so that the cost of both building and tessellating the shapes is measured.
+//! The mean time and the number of tessellated vertices per shape are reported.
+//!
+//! Run with `cargo bench --bench painter --features bench`.
+
+#![allow(clippy::print_stdout)]
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use egui::{CentralPanel, Color32, Context, Frame, Pos2, RawInput, Rect, Stroke, Vec2};
+use egui_gizmo::bench::Painter3d;
+use egui_gizmo::StrokeStyle;
+use glam::{DMat4, DVec3};
+
+/// Number of frames run before measuring, so that egui memory and fonts are initialized
+const WARMUP_FRAMES: usize = 20;
+/// Minimum time spent measuring a single case
+const MEASURE_TIME: Duration = Duration::from_millis(500);
+
+const SCREEN_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+/// Number of shapes drawn per frame
+const SHAPES: usize = 100;
+/// Radius of the shapes in world units
+const RADIUS: f64 = 1.0;
+
+const COLOR: Color32 = Color32::from_rgba_premultiplied(100, 100, 200, 200);
+
+/// Number of line segments used for filling a shape, enough to cover it without gaps
+const FILL_LINES: usize = 64;
+
+#[derive(Copy, Clone, Debug)]
+enum Shape {
+    Circle,
+    Sector,
+    Polygon,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Method {
+    Filled,
+    Lines,
+}
+
+struct Measurement {
+    shape_time: Duration,
+    vertices: usize,
+}
+
+/// Outline of the polygon case, a plane handle like quad
+fn polygon() -> [DVec3; 4] {
+    [
+        DVec3::new(-RADIUS, 0.0, -RADIUS),
+        DVec3::new(RADIUS, 0.0, -RADIUS),
+        DVec3::new(RADIUS, 0.0, RADIUS),
+        DVec3::new(-RADIUS, 0.0, RADIUS),
+    ]
+}
+
+/// Point on the circle in the XZ plane, where the painter draws its arcs
+fn circle_point(angle: f64) -> DVec3 {
+    DVec3::new(angle.cos(), 0.0, angle.sin()) * RADIUS
+}
+
+fn draw(painter: &Painter3d, shape: Shape, method: Method) {
+    match (shape, method) {
+        (Shape::Circle, Method::Filled) => {
+            painter.circle_filled(RADIUS, COLOR);
+        }
+        (Shape::Sector, Method::Filled) => {
+            painter.sector_filled(RADIUS, 0.0, FRAC_PI_2, COLOR);
+        }
+        (Shape::Polygon, Method::Filled) => {
+            painter.polygon_filled(&polygon(), COLOR);
+        }
+        (Shape::Circle | Shape::Sector, Method::Lines) => {
+            let sweep = if matches!(shape, Shape::Circle) {
+                TAU
+            } else {
+                FRAC_PI_2
+            };
+            for i in 0..FILL_LINES {
+                let angle = sweep * i as f64 / (FILL_LINES - 1) as f64;
+                painter.line_segment(
+                    DVec3::ZERO,
+                    circle_point(angle),
+                    Stroke::new(4.0, COLOR),
+                    StrokeStyle::Solid,
+                );
+            }
+            painter.arc(RADIUS, 0.0, sweep, (1.0, COLOR), StrokeStyle::Solid);
+        }
+        (Shape::Polygon, Method::Lines) => {
+            let [a, b, c, d] = polygon();
+            for i in 0..FILL_LINES {
+                let t = i as f64 / (FILL_LINES - 1) as f64;
+                painter.line_segment(
+                    a.lerp(d, t),
+                    b.lerp(c, t),
+                    Stroke::new(4.0, COLOR),
+                    StrokeStyle::Solid,
+                );
+            }
+        }
+    }
+}
+
+fn frame(ctx: &Context, time: f64, shape: Shape, method: Method) -> usize {
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, SCREEN_SIZE)),
+        time: Some(time),
+        ..Default::default()
+    };
+
+    let view = DMat4::look_at_rh(DVec3::new(0.0, 3.0, 3.0), DVec3::ZERO, DVec3::Y);
+    let projection = DMat4::perspective_rh(
+        45_f64.to_radians(),
+        (SCREEN_SIZE.x / SCREEN_SIZE.y) as f64,
+        0.1,
+        100.0,
+    );
+
+    let output = ctx.run(input, |ctx| {
+        CentralPanel::default()
+            .frame(Frame::none())
+            .show(ctx, |ui| {
+                let painter =
+                    Painter3d::new(ui.painter().clone(), projection * view, ui.clip_rect());
+                for _ in 0..SHAPES {
+                    draw(&painter, shape, method);
+                }
+            });
+    });
+
+    let primitives = ctx.tessellate(output.shapes, output.pixels_per_point);
+    black_box(primitives)
+        .iter()
+        .map(|primitive| match &primitive.primitive {
+            egui::epaint::Primitive::Mesh(mesh) => mesh.vertices.len(),
+            egui::epaint::Primitive::Callback(_) => 0,
+        })
+        .sum()
+}
+
+fn measure(shape: Shape, method: Method) -> Measurement {
+    let ctx = Context::default();
+    let mut time = 0.0;
+
+    for _ in 0..WARMUP_FRAMES {
+        frame(&ctx, time, shape, method);
+        time += 1.0 / 60.0;
+    }
+
+    let start = Instant::now();
+    let mut frames = 0;
+    let mut vertices = 0;
+    while start.elapsed() < MEASURE_TIME {
+        vertices = frame(&ctx, time, shape, method);
+        time += 1.0 / 60.0;
+        frames += 1;
+    }
+
+    Measurement {
+        shape_time: start.elapsed() / (frames * SHAPES as u32),
+        vertices: vertices / SHAPES,
+    }
+}
+
+fn main() {
+    // Arguments that are not flags filter the cases by name, like in the default harness
+    let filters: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+
+    println!("{:<32} {:>14} {:>14}", "case", "shape time", "vertices");
+
+    for shape in [Shape::Circle, Shape::Sector, Shape::Polygon] {
+        for method in [Method::Filled, Method::Lines] {
+            let name = format!("{shape:?}/{method:?}").to_lowercase();
+            if !filters.is_empty() && !filters.iter().any(|filter| name.contains(filter)) {
+                continue;
+            }
+
+            let measurement = measure(shape, method);
+            println!(
+                "{:<32} {:>11.2} µs {:>14}",
+                name,
+                measurement.shape_time.as_secs_f64() * 1e6,
+                measurement.vertices
+            );
+        }
+    }
+}
@@ -6,8 +6,9 @@ use bevy_infinite_grid::{InfiniteGridBundle, InfiniteGridPlugin, InfiniteGridSet
 use egui::color_picker::Alpha;
 use egui::{pos2, Align2, Color32, FontId, LayerId, Ui, Widget};
 
+use egui_gizmo::enumset::EnumSet;
 use egui_gizmo::{
-    Gizmo, GizmoMode, GizmoOrientation, GizmoResult, GizmoVisuals, DEFAULT_SNAP_ANGLE,
+    AxisTriad, Gizmo, GizmoMode, GizmoOrientation, GizmoResult, GizmoVisuals, DEFAULT_SNAP_ANGLE,
     DEFAULT_SNAP_DISTANCE,
 };
 
@@ -40,10 +41,14 @@ fn main() {
 
 #[derive(Resource)]
 struct GizmoOptions {
-    gizmo_mode: GizmoMode,
+    gizmo_modes: EnumSet<GizmoMode>,
     gizmo_orientation: GizmoOrientation,
     last_result: Option<GizmoResult>,
     custom_highlight_color: bool,
+    arcball_sensitivity: f32,
+    double_click_resets: bool,
+    show_tooltips: bool,
+    flip_y: bool,
     visuals: GizmoVisuals,
 }
 
@@ -57,15 +62,20 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     commands.insert_resource(GizmoOptions {
-        gizmo_mode: GizmoMode::Rotate,
+        gizmo_modes: EnumSet::only(GizmoMode::Rotate),
         gizmo_orientation: GizmoOrientation::Global,
         last_result: None,
         custom_highlight_color: false,
+        arcball_sensitivity: 1.0,
+        double_click_resets: true,
+        show_tooltips: true,
+        flip_y: false,
         visuals: GizmoVisuals {
             x_color: Color32::from_rgb(255, 0, 148),
             y_color: Color32::from_rgb(148, 255, 0),
             z_color: Color32::from_rgb(0, 148, 255),
-            s_color: Color32::WHITE,
+            view_ring_color: Some(Color32::WHITE),
+            view_plane_color: Some(Color32::WHITE),
             ..default()
         },
     });
@@ -130,17 +140,23 @@ fn update(
     egui::Window::new("Settings")
         .resizable(false)
         .show(contexts.ctx_mut(), |ui| {
-            egui::ComboBox::from_label("Mode")
-                .selected_text(format!("{:?}", gizmo_options.gizmo_mode))
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(&mut gizmo_options.gizmo_mode, GizmoMode::Rotate, "Rotate");
-                    ui.selectable_value(
-                        &mut gizmo_options.gizmo_mode,
-                        GizmoMode::Translate,
-                        "Translate",
-                    );
-                    ui.selectable_value(&mut gizmo_options.gizmo_mode, GizmoMode::Scale, "Scale");
-                });
+            ui.horizontal(|ui| {
+                for (mode, label) in [
+                    (GizmoMode::Rotate, "Rotate"),
+                    (GizmoMode::Translate, "Translate"),
+                    (GizmoMode::Scale, "Scale"),
+                    (GizmoMode::Arcball, "Arcball"),
+                ] {
+                    let mut enabled = gizmo_options.gizmo_modes.contains(mode);
+                    if ui.toggle_value(&mut enabled, label).changed() {
+                        if enabled {
+                            gizmo_options.gizmo_modes.insert(mode);
+                        } else {
+                            gizmo_options.gizmo_modes.remove(mode);
+                        }
+                    }
+                }
+            });
             ui.end_row();
 
             egui::ComboBox::from_label("Orientation")
@@ -167,6 +183,12 @@ fn update(
             egui::Slider::new(&mut gizmo_options.visuals.stroke_width, 0.1..=10.0)
                 .text("Stroke width")
                 .ui(ui);
+            egui::Slider::new(&mut gizmo_options.visuals.arcball_radius, 0.1..=2.0)
+                .text("Arcball radius")
+                .ui(ui);
+            egui::Slider::new(&mut gizmo_options.arcball_sensitivity, 0.1..=2.0)
+                .text("Arcball sensitivity")
+                .ui(ui);
             egui::Slider::new(&mut gizmo_options.visuals.inactive_alpha, 0.0..=1.0)
                 .text("Inactive alpha")
                 .ui(ui);
@@ -174,6 +196,33 @@ fn update(
                 .text("Highlighted alpha")
                 .ui(ui);
 
+            egui::Checkbox::new(
+                &mut gizmo_options.double_click_resets,
+                "Double-click resets",
+            )
+            .ui(ui);
+            egui::Checkbox::new(&mut gizmo_options.show_tooltips, "Show tooltips").ui(ui);
+            egui::Checkbox::new(&mut gizmo_options.flip_y, "Flipped Y projection").ui(ui);
+            let mut dim_inactive = gizmo_options.visuals.dim_inactive_during_drag.is_some();
+            if egui::Checkbox::new(&mut dim_inactive, "Dim inactive during drag")
+                .ui(ui)
+                .changed()
+            {
+                gizmo_options.visuals.dim_inactive_during_drag = dim_inactive.then_some(0.25);
+            }
+            egui::Checkbox::new(
+                &mut gizmo_options.visuals.show_negative_axes,
+                "Show negative axes",
+            )
+            .ui(ui);
+            egui::Checkbox::new(&mut gizmo_options.visuals.show_value, "Show value").ui(ui);
+            egui::Checkbox::new(&mut gizmo_options.visuals.show_snap_grid, "Show snap grid").ui(ui);
+            egui::Checkbox::new(
+                &mut gizmo_options.visuals.show_snap_ruler,
+                "Show snap ruler",
+            )
+            .ui(ui);
+
             ui.horizontal(|ui| {
                 egui::color_picker::color_edit_button_srgba(
                     ui,
@@ -218,10 +267,24 @@ fn update(
             ui.horizontal(|ui| {
                 egui::color_picker::color_edit_button_srgba(
                     ui,
-                    &mut gizmo_options.visuals.s_color,
+                    gizmo_options
+                        .visuals
+                        .view_ring_color
+                        .get_or_insert(Color32::WHITE),
+                    Alpha::Opaque,
+                );
+                egui::Label::new("View ring color").wrap(false).ui(ui);
+            });
+            ui.horizontal(|ui| {
+                egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    gizmo_options
+                        .visuals
+                        .view_plane_color
+                        .get_or_insert(Color32::WHITE),
                     Alpha::Opaque,
                 );
-                egui::Label::new("Screen axis color").wrap(false).ui(ui);
+                egui::Label::new("View plane color").wrap(false).ui(ui);
             });
             ui.end_row();
 
@@ -263,15 +326,27 @@ fn update(
 
                 let model_matrix = target_q.single_mut().compute_matrix();
 
+                // The same scene can be given to the gizmo with a Vulkan style
+                // projection, where the Y axis is flipped.
+                let projection_matrix = if gizmo_options.flip_y {
+                    Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0)) * projection_matrix
+                } else {
+                    projection_matrix
+                };
+
                 let gizmo = Gizmo::new("My gizmo")
-                    .view_matrix(view_matrix.to_cols_array_2d().into())
-                    .projection_matrix(projection_matrix.to_cols_array_2d().into())
-                    .model_matrix(model_matrix.to_cols_array_2d().into())
-                    .mode(gizmo_options.gizmo_mode)
+                    .view_matrix(view_matrix.to_cols_array_2d())
+                    .projection_matrix(projection_matrix.to_cols_array_2d())
+                    .flip_y(gizmo_options.flip_y)
+                    .model_matrix(model_matrix.to_cols_array_2d())
+                    .modes(gizmo_options.gizmo_modes)
                     .orientation(gizmo_options.gizmo_orientation)
                     .snapping(snapping)
                     .snap_angle(snap_angle)
                     .snap_distance(snap_distance)
+                    .arcball_sensitivity(gizmo_options.arcball_sensitivity)
+                    .double_click_resets(gizmo_options.double_click_resets)
+                    .show_tooltips(gizmo_options.show_tooltips)
                     .visuals(visuals);
 
                 gizmo_options.last_result = gizmo.interact(ui);
@@ -287,6 +362,19 @@ fn update(
                 }
 
                 instructions_text(ui);
+
+                let viewport = ui.clip_rect();
+                AxisTriad::new()
+                    .view_matrix(view_matrix.to_cols_array_2d())
+                    .projection_matrix(projection_matrix.to_cols_array_2d())
+                    .visuals(visuals)
+                    .paint(
+                        ui,
+                        egui::Rect::from_min_size(
+                            viewport.right_top() - egui::vec2(90.0, 0.0),
+                            egui::vec2(90.0, 90.0),
+                        ),
+                    );
             });
         });
 }
@@ -306,7 +394,7 @@ fn instructions_text(ui: &Ui) {
 fn show_gizmo_status(ui: &Ui, response: GizmoResult) {
     let text = if let Some(value) = response.value {
         match response.mode {
-            GizmoMode::Rotate => {
+            GizmoMode::Rotate | GizmoMode::Arcball => {
                 let length = Vec3::from(value).length();
                 format!("{:.1}°, {:.2} rad", length.to_degrees(), length)
             }
@@ -0,0 +1,70 @@
+//! Drives a gizmo without a window or renderer by feeding egui simulated input.
+//!
+//! The pointer presses the X arrow of the translation gizmo and drags it to the right.
+//! The same approach can be used for testing applications that embed the gizmo.
+
+#![allow(clippy::print_stdout)]
+
+use egui::{CentralPanel, Context, Event, Frame, PointerButton, Pos2, RawInput, Rect, Vec2};
+use egui_gizmo::{Gizmo, GizmoMode};
+use glam::{Mat4, Vec3};
+
+fn main() {
+    let ctx = Context::default();
+    let screen_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+
+    let view_matrix = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+    let projection_matrix =
+        Mat4::perspective_rh(45_f32.to_radians(), screen_rect.aspect_ratio(), 0.1, 100.0);
+    let mut model_matrix = Mat4::IDENTITY;
+
+    // The camera looks at the gizmo, so the X arrow points right from the center of the screen
+    let start = screen_rect.center() + Vec2::new(50.0, 0.0);
+    let end = start + Vec2::new(100.0, 0.0);
+    let button = |pos, pressed| Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    };
+
+    let frames = [
+        vec![Event::PointerMoved(start)],
+        vec![button(start, true)],
+        vec![Event::PointerMoved(start.lerp(end, 0.5))],
+        vec![Event::PointerMoved(end)],
+        vec![button(end, false)],
+    ];
+
+    for (frame, events) in frames.into_iter().enumerate() {
+        let input = RawInput {
+            screen_rect: Some(screen_rect),
+            time: Some(frame as f64 / 60.0),
+            events,
+            ..Default::default()
+        };
+
+        let _ = ctx.run(input, |ctx| {
+            CentralPanel::default()
+                .frame(Frame::none())
+                .show(ctx, |ui| {
+                    let interaction = Gizmo::new("gizmo")
+                        .view_matrix(view_matrix.to_cols_array_2d())
+                        .projection_matrix(projection_matrix.to_cols_array_2d())
+                        .model_matrix(model_matrix.to_cols_array_2d())
+                        .mode(GizmoMode::Translate)
+                        .interact_detailed(ui);
+
+                    if let Some(result) = interaction.result {
+                        model_matrix = Mat4::from(result.transform());
+                    }
+
+                    let hovered = interaction.hovered.map(|handle| handle.to_string());
+                    println!("frame {frame}: hovered {hovered:?}");
+                });
+        });
+    }
+
+    let (_, _, translation) = model_matrix.to_scale_rotation_translation();
+    println!("final translation: {translation}");
+}
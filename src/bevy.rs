@@ -0,0 +1,45 @@
+//! Conversions between Bevy types and the gizmo. Enabled with the `bevy` feature.
+
+use bevy_math::{Mat4, Quat, Vec3};
+use bevy_transform::components::{GlobalTransform, Transform};
+
+use crate::{Gizmo, GizmoResult};
+
+impl Gizmo {
+    /// Translation, rotation and scale of the gizmo from a Bevy [`Transform`].
+    pub fn bevy_transform(self, transform: &Transform) -> Self {
+        self.transform(
+            transform.translation.to_array().into(),
+            transform.rotation.to_array().into(),
+            transform.scale.to_array().into(),
+        )
+    }
+
+    /// View and projection matrices of the gizmo from a Bevy camera.
+    ///
+    /// The view matrix is the inverse of the camera's [`GlobalTransform`].
+    /// The projection matrix is usually obtained from `Camera::projection_matrix`,
+    /// which is kept in sync with the camera's `Projection` component.
+    /// Bevy uses a right-handed coordinate system with a reversed, infinite depth range,
+    /// which the gizmo detects from the projection matrix itself.
+    pub fn bevy_camera(self, camera_transform: &GlobalTransform, projection_matrix: Mat4) -> Self {
+        self.view_matrix(
+            camera_transform
+                .compute_matrix()
+                .inverse()
+                .to_cols_array_2d(),
+        )
+        .projection_matrix(projection_matrix.to_cols_array_2d())
+    }
+}
+
+impl GizmoResult {
+    /// Updated transformation as a Bevy [`Transform`].
+    pub fn to_bevy_transform(&self) -> Transform {
+        Transform {
+            translation: Vec3::from_array(self.translation.into()),
+            rotation: Quat::from_array(self.rotation.into()),
+            scale: Vec3::from_array(self.scale.into()),
+        }
+    }
+}
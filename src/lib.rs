@@ -21,27 +21,82 @@
 //! The gizmo can be placed inside a container such as a [`egui::Window`] or an [`egui::Area`].
 //! By default, the gizmo will use the ui clip rect as a viewport.
 //! The gizmo will apply transformations to the given model matrix.
+//!
+//! ## Coordinate systems
+//! Matrices can be given for either right handed or left handed coordinate systems.
+//! The handedness is detected from the projection matrix, and the gizmo behaves the same
+//! in both: the handles are placed along the positive axes of the model and a drag results
+//! in the same transformation as in a mirror image of the scene with the other handedness.
 
+use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, PI};
 use std::hash::Hash;
 use std::ops::Sub;
+use std::rc::Rc;
 
-use crate::math::{screen_to_world, world_to_screen};
-use egui::{Color32, Context, Id, PointerButton, Pos2, Rect, Sense, Ui};
-use glam::{DMat4, DQuat, DVec3, Mat4, Quat, Vec3, Vec4Swizzles};
+use crate::math::{
+    matrix_is_trs, orthonormalized_trs, round_to_interval, screen_to_world, world_to_screen,
+};
+use crate::painter::Painter3d;
+use egui::util::IdTypeMap;
+use egui::{
+    Align2, Color32, Context, CursorIcon, EventFilter, FontId, Id, Key, Modifiers, Painter,
+    PointerButton, Pos2, Rect, Response, Sense, Ui, Vec2, WidgetInfo, WidgetType,
+};
+use enumset::{EnumSet, EnumSetType};
+use glam::{DMat3, DMat4, DQuat, DVec3, Mat4, Quat, Vec3, Vec4Swizzles};
 
 use crate::subgizmo::rotation::RotationParams;
 use crate::subgizmo::scale::ScaleParams;
 use crate::subgizmo::translation::TranslationParams;
 use crate::subgizmo::{
-    ArcballSubGizmo, RotationSubGizmo, ScaleSubGizmo, SubGizmo, TransformKind, TranslationSubGizmo,
+    ArcballSubGizmo, RotationSubGizmo, ScaleSubGizmo, SubGizmo, TranslationSubGizmo,
 };
 
+#[cfg(feature = "bevy")]
+mod bevy;
 mod math;
 mod painter;
+pub mod prelude;
 mod subgizmo;
+mod triad;
+pub use egui;
+pub use enumset;
+pub use math::{is_trs, pick_polygon, pick_polyline};
 pub use mint;
+pub use triad::AxisTriad;
+
+/// Internals used by the benchmarks, not part of the public API
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    pub use crate::painter::Painter3d;
+
+    static MEMORY_ACCESSES: AtomicUsize = AtomicUsize::new(0);
+    static STATE_ACCESSES: AtomicUsize = AtomicUsize::new(0);
+
+    /// Number of times the gizmos have accessed egui memory
+    pub fn memory_accesses() -> usize {
+        MEMORY_ACCESSES.load(Ordering::Relaxed)
+    }
+
+    /// Number of times the subgizmos have read or written their state. Each of these
+    /// accessed egui memory before the states were loaded once per frame.
+    pub fn state_accesses() -> usize {
+        STATE_ACCESSES.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn count_memory_access() {
+        MEMORY_ACCESSES.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn count_state_access() {
+        STATE_ACCESSES.fetch_add(1, Ordering::Relaxed);
+    }
+}
 
 /// The default snapping distance for rotation in radians
 pub const DEFAULT_SNAP_ANGLE: f32 = PI / 32.0;
@@ -49,37 +104,200 @@ pub const DEFAULT_SNAP_ANGLE: f32 = PI / 32.0;
 pub const DEFAULT_SNAP_DISTANCE: f32 = 0.1;
 /// The default snapping distance for scale
 pub const DEFAULT_SNAP_SCALE: f32 = 0.1;
+/// The default coarse snapping distance for rotation in radians
+pub const DEFAULT_COARSE_SNAP_ANGLE: f32 = FRAC_PI_2;
+/// The default coarse snapping distance for translation
+pub const DEFAULT_COARSE_SNAP_DISTANCE: f32 = 1.0;
+/// The default distance in points the pointer needs to move before a drag starts
+pub const DEFAULT_DRAG_THRESHOLD: f32 = 2.0;
+
+/// Length of the axis lines of a compact gizmo, relative to the gizmo size
+const COMPACT_AXIS_LENGTH: f32 = 0.3;
+
+/// Scroll distance in points per nudge step, about one notch of a mouse wheel
+const SCROLL_POINTS_PER_NUDGE: f32 = 50.0;
+
+/// Factor by which one notch of a mouse wheel grows the proportional editing radius
+const PROPORTIONAL_RADIUS_SCROLL_FACTOR: f32 = 1.25;
+
+/// Tolerance of the model matrix for shear and perspective terms, see [`is_trs`]
+const TRS_EPSILON: f64 = 1e-3;
+
+/// Alpha multiplier of the original pose drawn under the dragged pose, see [`Gizmo::preview_only`]
+const PREVIEW_ORIGINAL_ALPHA: f32 = 0.35;
 
 pub struct Gizmo {
     id: Id,
     config: GizmoConfig,
     subgizmos: Vec<Box<dyn SubGizmo>>,
+    snap_fn: Option<Rc<SnapFn>>,
+    placement_fn: Option<Rc<PlacementFn>>,
+    tooltip_fn: Option<Rc<TooltipFn>>,
+    visuals_overrides: Vec<(GizmoDirection, TransformKind, GizmoVisualsOverride)>,
+    subgizmo_states: Rc<RefCell<IdTypeMap>>,
 }
 
 impl Gizmo {
+    /// Creates a new gizmo. The id only needs to be unique within the [`Ui`] the
+    /// gizmo is shown in, so gizmos in different panels can use the same id,
+    /// e.g. when they manipulate the same object.
     pub fn new(id_source: impl Hash) -> Self {
         Self {
             id: Id::new(id_source),
             config: GizmoConfig::default(),
             subgizmos: Default::default(),
+            snap_fn: None,
+            placement_fn: None,
+            tooltip_fn: None,
+            visuals_overrides: Vec::new(),
+            subgizmo_states: Default::default(),
         }
     }
 
-    /// Matrix that specifies translation and rotation of the gizmo in world space
-    pub fn model_matrix(mut self, model_matrix: mint::ColumnMatrix4<f32>) -> Self {
-        self.config.model_matrix = Mat4::from(model_matrix).as_dmat4();
+    /// Matrix that specifies translation and rotation of the gizmo in world space,
+    /// or relative to the parent given with [`Gizmo::parent_matrix`].
+    ///
+    /// Plain `[[f32; 4]; 4]` and `[f32; 16]` arrays are accepted as well, and are
+    /// interpreted in column major order. See [`Gizmo::model_matrix_row_major`]
+    /// for matrices in row major order.
+    pub fn model_matrix(mut self, model_matrix: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+        self.config.model_matrix = Mat4::from(model_matrix.into()).as_dmat4();
+        self.config.decomposed = false;
+        self
+    }
+
+    /// Translation, rotation and scale of the gizmo in world space. This can be
+    /// used instead of [`Gizmo::model_matrix`] when the transformation is already
+    /// stored as separate components, avoiding the decomposition of the matrix.
+    pub fn transform(
+        mut self,
+        translation: mint::Vector3<f32>,
+        rotation: mint::Quaternion<f32>,
+        scale: mint::Vector3<f32>,
+    ) -> Self {
+        self.config.translation = Vec3::from(translation).as_dvec3();
+        self.config.rotation = Quat::from(rotation).as_dquat();
+        self.config.scale = Vec3::from(scale).as_dvec3();
+        self.config.model_matrix = DMat4::from_scale_rotation_translation(
+            self.config.scale,
+            self.config.rotation,
+            self.config.translation,
+        );
+        self.config.decomposed = true;
+        self
+    }
+
+    /// World transformation of the parent of the edited object, for editing the transformation
+    /// of the object relative to its parent. [`Gizmo::model_matrix`] is then the local
+    /// transformation, and the gizmo is shown at `parent_matrix * model_matrix`.
+    ///
+    /// The results are given as local transformations, see [`GizmoResult::local_transform`].
+    /// [`GizmoOrientation::Local`] follows the axes of the object and
+    /// [`GizmoOrientation::Global`] the world axes, as without a parent.
+    pub fn parent_matrix(mut self, parent_matrix: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+        self.config.parent_matrix = Some(Mat4::from(parent_matrix.into()).as_dmat4());
+        self
+    }
+
+    /// Matrix that specifies translation and rotation of the viewport camera.
+    ///
+    /// Plain `[[f32; 4]; 4]` and `[f32; 16]` arrays are accepted as well, and are
+    /// interpreted in column major order. See [`Gizmo::view_matrix_row_major`]
+    /// for matrices in row major order.
+    pub fn view_matrix(mut self, view_matrix: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+        self.config.view_matrix = Mat4::from(view_matrix.into()).as_dmat4();
+        self.config.camera_prepared = false;
+        self
+    }
+
+    /// Matrix that specifies projection of the viewport.
+    ///
+    /// Both right handed and left handed projections are supported, and the handedness
+    /// is detected from the matrix. Dragging a subgizmo gives the same transformation in
+    /// both, e.g. rotating around an axis is counterclockwise when the axis points
+    /// towards the viewer with a right handed projection, and clockwise with a left handed one.
+    ///
+    /// Plain `[[f32; 4]; 4]` and `[f32; 16]` arrays are accepted as well, and are
+    /// interpreted in column major order. See [`Gizmo::projection_matrix_row_major`]
+    /// for matrices in row major order.
+    pub fn projection_matrix(
+        mut self,
+        projection_matrix: impl Into<mint::ColumnMatrix4<f32>>,
+    ) -> Self {
+        self.config.projection_matrix = Mat4::from(projection_matrix.into()).as_dmat4();
+        self.config.camera_prepared = false;
+        self
+    }
+
+    /// Whether the projection matrix flips the Y axis, as is common with Vulkan and wgpu.
+    /// Normalized device coordinates are then expected to have Y pointing down.
+    pub const fn flip_y(mut self, flip_y: bool) -> Self {
+        self.config.flip_y = flip_y;
+        self.config.camera_prepared = false;
+        self
+    }
+
+    /// Overrides the viewing direction used for the [`GizmoDirection::View`] handles and
+    /// other view aligned drawing, such as when the viewer of a VR headset looks in a
+    /// different direction than the camera rendering the viewport. `forward` is the
+    /// direction the viewer looks at and `up` points up from the viewer, in world space.
+    ///
+    /// The view and projection matrices are still used for projecting and picking.
+    pub fn view_override(
+        mut self,
+        forward: impl Into<mint::Vector3<f32>>,
+        up: impl Into<mint::Vector3<f32>>,
+    ) -> Self {
+        self.config.view_override = Some((
+            Vec3::from(forward.into()).as_dvec3(),
+            Vec3::from(up.into()).as_dvec3(),
+        ));
         self
     }
 
-    /// Matrix that specifies translation and rotation of the viewport camera
-    pub fn view_matrix(mut self, view_matrix: mint::ColumnMatrix4<f32>) -> Self {
-        self.config.view_matrix = Mat4::from(view_matrix).as_dmat4();
+    /// Same as [`Gizmo::model_matrix`], but plain arrays are interpreted in row major order
+    pub fn model_matrix_row_major(
+        mut self,
+        model_matrix: impl Into<mint::RowMatrix4<f32>>,
+    ) -> Self {
+        self.config.model_matrix = Mat4::from(model_matrix.into()).as_dmat4();
+        self.config.decomposed = false;
         self
     }
 
-    /// Matrix that specifies projection of the viewport
-    pub fn projection_matrix(mut self, projection_matrix: mint::ColumnMatrix4<f32>) -> Self {
-        self.config.projection_matrix = Mat4::from(projection_matrix).as_dmat4();
+    /// Same as [`Gizmo::view_matrix`], but plain arrays are interpreted in row major order
+    pub fn view_matrix_row_major(mut self, view_matrix: impl Into<mint::RowMatrix4<f32>>) -> Self {
+        self.config.view_matrix = Mat4::from(view_matrix.into()).as_dmat4();
+        self.config.camera_prepared = false;
+        self
+    }
+
+    /// Same as [`Gizmo::projection_matrix`], but plain arrays are interpreted in row major order
+    pub fn projection_matrix_row_major(
+        mut self,
+        projection_matrix: impl Into<mint::RowMatrix4<f32>>,
+    ) -> Self {
+        self.config.projection_matrix = Mat4::from(projection_matrix.into()).as_dmat4();
+        self.config.camera_prepared = false;
+        self
+    }
+
+    /// Camera configuration shared with other gizmos drawn in the same viewport.
+    /// This replaces the view and projection matrices, the viewport, the visuals
+    /// and the snapping configuration of this gizmo.
+    pub const fn with_camera(mut self, camera: &GizmoCameraConfig) -> Self {
+        self.config.view_matrix = camera.view_matrix;
+        self.config.projection_matrix = camera.projection_matrix;
+        self.config.flip_y = camera.flip_y;
+        self.config.viewport = camera.viewport;
+        self.config.visuals = camera.visuals;
+        self.config.snapping = camera.snapping;
+        self.config.snap_angle = camera.snap_angle;
+        self.config.snap_distance = camera.snap_distance;
+        self.config.snap_scale = camera.snap_scale;
+        self.config.view_projection = camera.view_projection;
+        self.config.left_handed = camera.left_handed;
+        self.config.camera_prepared = true;
         self
     }
 
@@ -90,8 +308,60 @@ impl Gizmo {
     }
 
     /// Gizmo mode to use
-    pub const fn mode(mut self, mode: GizmoMode) -> Self {
-        self.config.mode = mode;
+    pub fn mode(mut self, mode: GizmoMode) -> Self {
+        self.config.modes = EnumSet::only(mode);
+        self
+    }
+
+    /// Gizmo modes to use. Subgizmos of every given mode are shown at the same time.
+    pub const fn modes(mut self, modes: EnumSet<GizmoMode>) -> Self {
+        self.config.modes = modes;
+        self
+    }
+
+    /// Plane subgizmos to show for translation and scale. All planes are shown by default.
+    pub const fn planes(mut self, planes: EnumSet<GizmoPlane>) -> Self {
+        self.config.planes = planes;
+        self
+    }
+
+    /// Preset for objects that move on a plane, such as props on the ground. Shows a single
+    /// translation plane subgizmo centered on the gizmo origin, and the translation axis
+    /// perpendicular to it. The side length of the plane is `plane_size` times the gizmo size.
+    pub fn planar_translation(mut self, plane: GizmoPlane, plane_size: f32) -> Self {
+        self.config.modes = EnumSet::only(GizmoMode::Translate);
+        self.config.planes = EnumSet::only(plane);
+        self.config.plane_placement = PlanePlacement::Centered;
+        self.config.planar = Some((plane, plane_size));
+        self
+    }
+
+    /// Function that gives the draw order of each subgizmo. Subgizmos with a larger
+    /// value are drawn on top of others. [`GizmoHandle::default_draw_order`] by default.
+    pub const fn draw_order(mut self, draw_order: fn(GizmoHandle) -> i32) -> Self {
+        self.config.draw_order = draw_order;
+        self
+    }
+
+    /// Function that gives the pick priority of each subgizmo. When several subgizmos
+    /// are under the pointer, the one with the largest value is picked, or the one
+    /// closest to the camera if the values are equal. All subgizmos have the same
+    /// priority by default.
+    pub const fn pick_priority(mut self, pick_priority: fn(GizmoHandle) -> i32) -> Self {
+        self.config.pick_priority = pick_priority;
+        self
+    }
+
+    /// Whether the view aligned subgizmos are shown, such as the view translation plane
+    /// and the view rotation ring. Shown by default.
+    pub const fn show_view_handles(mut self, show_view_handles: bool) -> Self {
+        self.config.show_view_handles = show_view_handles;
+        self
+    }
+
+    /// Placement of the plane subgizmos relative to the gizmo origin
+    pub const fn plane_placement(mut self, plane_placement: PlanePlacement) -> Self {
+        self.config.plane_placement = plane_placement;
         self
     }
 
@@ -107,6 +377,33 @@ impl Gizmo {
         self
     }
 
+    /// Whether rotation, including the arcball, is snapped when snapping is enabled.
+    /// Enabled by default, so that rotation follows [`Gizmo::snapping`].
+    pub fn snapping_rotate(self, snapping: bool) -> Self {
+        self.snapping_mode(GizmoMode::Rotate, snapping)
+    }
+
+    /// Whether translation is snapped when snapping is enabled.
+    /// Enabled by default, so that translation follows [`Gizmo::snapping`].
+    pub fn snapping_translate(self, snapping: bool) -> Self {
+        self.snapping_mode(GizmoMode::Translate, snapping)
+    }
+
+    /// Whether scaling is snapped when snapping is enabled.
+    /// Enabled by default, so that scaling follows [`Gizmo::snapping`].
+    pub fn snapping_scale(self, snapping: bool) -> Self {
+        self.snapping_mode(GizmoMode::Scale, snapping)
+    }
+
+    fn snapping_mode(mut self, mode: GizmoMode, snapping: bool) -> Self {
+        if snapping {
+            self.config.snap_modes.insert(mode);
+        } else {
+            self.config.snap_modes.remove(mode);
+        }
+        self
+    }
+
     /// Snap angle to use for rotation when snapping is enabled
     pub const fn snap_angle(mut self, snap_angle: f32) -> Self {
         self.config.snap_angle = snap_angle;
@@ -125,133 +422,1293 @@ impl Gizmo {
         self
     }
 
+    /// Modifier keys that switch to coarse snapping while held, even when snapping is disabled.
+    /// The coarse snap angle and distance then replace the regular ones. None by default.
+    pub const fn coarse_snap_modifier(mut self, modifiers: Modifiers) -> Self {
+        self.config.coarse_snap_modifier = Some(modifiers);
+        self
+    }
+
+    /// Snap angle to use for rotation when the coarse snap modifier is held
+    pub const fn coarse_snap_angle(mut self, coarse_snap_angle: f32) -> Self {
+        self.config.coarse_snap_angle = coarse_snap_angle;
+        self
+    }
+
+    /// Snap distance to use for translation when the coarse snap modifier is held
+    pub const fn coarse_snap_distance(mut self, coarse_snap_distance: f32) -> Self {
+        self.config.coarse_snap_distance = coarse_snap_distance;
+        self
+    }
+
+    /// How the scale is snapped when snapping is enabled.
+    /// [`ScaleSnapMode::Additive`] with [`Gizmo::snap_scale`] by default.
+    pub const fn scale_snap_mode(mut self, scale_snap_mode: ScaleSnapMode) -> Self {
+        self.config.scale_snap_mode = scale_snap_mode;
+        self
+    }
+
+    /// Modifier keys that make the scale uniform while held, when dragging any of the
+    /// axis or plane scale subgizmos. The scale is always measured from the start of
+    /// the drag, so releasing the modifiers returns to the constrained scale. None by default.
+    pub const fn uniform_scale_modifier(mut self, modifiers: Modifiers) -> Self {
+        self.config.uniform_scale_modifier = Some(modifiers);
+        self
+    }
+
+    /// Point of the object that stays in place when scaling.
+    /// [`ScaleOrigin::Center`] by default. The sides of the object are
+    /// given with [`Gizmo::local_bounds`].
+    pub const fn scale_origin(mut self, scale_origin: ScaleOrigin) -> Self {
+        self.config.scale_origin = scale_origin;
+        self
+    }
+
+    /// Bounding box of the object in its local space, before the model transformation
+    pub fn local_bounds(mut self, min: mint::Vector3<f32>, max: mint::Vector3<f32>) -> Self {
+        self.config.local_bounds = Some((Vec3::from(min).as_dvec3(), Vec3::from(max).as_dvec3()));
+        self
+    }
+
+    /// Function used for snapping translation to points given by the application,
+    /// such as vertices or surfaces of the scene. It is called while translating
+    /// with snapping enabled. When it returns a point, the point is projected onto
+    /// the active axis or plane and used instead of the snap distance.
+    pub fn snap_fn(
+        mut self,
+        snap_fn: impl Fn(SnapQuery) -> Option<mint::Vector3<f32>> + 'static,
+    ) -> Self {
+        self.snap_fn = Some(Rc::new(snap_fn));
+        self
+    }
+
+    /// Function used for placing the object on surfaces of the scene, such as the ground.
+    /// It is called with the pointer ray while translating with the placement modifier
+    /// held, and returns a world space position and surface normal. The translation is
+    /// then the returned position, regardless of the active axis or plane.
+    /// The modifier does nothing without the function.
+    pub fn placement_fn(
+        mut self,
+        placement_fn: impl Fn(PlacementQuery) -> Option<(mint::Vector3<f32>, mint::Vector3<f32>)>
+            + 'static,
+    ) -> Self {
+        self.placement_fn = Some(Rc::new(placement_fn));
+        self
+    }
+
+    /// Modifier keys that place the object with the function given with
+    /// [`Gizmo::placement_fn`] while held during a translation. Alt by default.
+    pub const fn placement_modifier(mut self, modifiers: Modifiers) -> Self {
+        self.config.placement_modifier = modifiers;
+        self
+    }
+
+    /// Whether placing the object with [`Gizmo::placement_fn`] also rotates it, so that
+    /// its Y axis is aligned with the surface normal. False by default.
+    pub const fn align_to_normal(mut self, align_to_normal: bool) -> Self {
+        self.config.align_to_normal = align_to_normal;
+        self
+    }
+
+    /// Pointer button used for dragging the subgizmos. Primary by default.
+    pub const fn drag_button(mut self, drag_button: PointerButton) -> Self {
+        self.config.drag_button = drag_button;
+        self
+    }
+
+    /// Whether the gizmo sets the cursor icon when a subgizmo is hovered or dragged
+    pub const fn set_cursor(mut self, set_cursor: bool) -> Self {
+        self.config.set_cursor = set_cursor;
+        self
+    }
+
+    /// Draws the given subgizmo highlighted as if it was hovered, e.g. for pointing it out
+    /// in a tutorial. Picking and dragging are not affected. [`None`] by default.
+    pub const fn force_highlight(mut self, handle: Option<GizmoHandle>) -> Self {
+        self.config.force_highlight = handle;
+        self
+    }
+
+    /// Radius for proportional editing in world units, drawn as a circle around the gizmo
+    /// in the [`GizmoVisuals::guide_stroke_style`]. Scrolling during a drag adjusts the radius, and the adjusted radius is
+    /// given in [`GizmoResult::proportional_radius`]. The gizmo does not apply any falloff
+    /// itself. [`None`] by default, which hides the circle.
+    pub const fn proportional_radius(mut self, proportional_radius: Option<f32>) -> Self {
+        self.config.proportional_radius = proportional_radius;
+        self
+    }
+
+    /// Whether scrolling over a subgizmo nudges it by the snapping steps, like the arrow
+    /// keys do when it is grabbed with the keyboard. The scroll is consumed by the gizmo.
+    /// Disabled by default.
+    pub const fn scroll_nudges(mut self, scroll_nudges: bool) -> Self {
+        self.config.scroll_nudges = scroll_nudges;
+        self
+    }
+
+    /// Whether a tooltip describing the action of the hovered subgizmo is shown
+    /// when the pointer rests on it. Disabled by default.
+    pub const fn show_tooltips(mut self, show_tooltips: bool) -> Self {
+        self.config.show_tooltips = show_tooltips;
+        self
+    }
+
+    /// Function giving the tooltip text of a subgizmo, for e.g. localization.
+    /// Returning [`None`] shows no tooltip for the subgizmo.
+    /// Defaults to [`GizmoHandle::default_tooltip`].
+    pub fn tooltip_text(
+        mut self,
+        tooltip_fn: impl Fn(GizmoHandle) -> Option<String> + 'static,
+    ) -> Self {
+        self.tooltip_fn = Some(Rc::new(tooltip_fn));
+        self
+    }
+
+    /// Distance in points from the edges of the subgizmos within which they are picked.
+    /// Defaults to [`egui::style::Interaction::resize_grab_radius_side`] of the ui style,
+    /// so that a style configured for touch input gives a touch friendly gizmo as well.
+    pub const fn pick_tolerance(mut self, pick_tolerance: f32) -> Self {
+        self.config.pick_tolerance = Some(pick_tolerance);
+        self
+    }
+
+    /// Distance in points that the pointer needs to move from the press position before
+    /// a drag starts transforming. Clicks that stay within the distance produce no result,
+    /// so they can be used for e.g. selecting the handle. [`DEFAULT_DRAG_THRESHOLD`] by default,
+    /// as the egui style has no drag threshold to follow.
+    pub const fn drag_threshold(mut self, drag_threshold: f32) -> Self {
+        self.config.drag_threshold = drag_threshold;
+        self
+    }
+
+    /// Whether the gizmo can be interacted with. A gizmo that is not interactable is drawn
+    /// with [`GizmoVisuals::disabled_alpha`], its subgizmos cannot be picked, focused or
+    /// dragged, and it gives no results. A drag in progress is ended when the gizmo becomes
+    /// non-interactable. Interactable by default.
+    pub const fn interactable(mut self, interactable: bool) -> Self {
+        self.config.interactable = interactable;
+        self
+    }
+
+    /// Whether the gizmo previews the transformation instead of expecting the host to apply
+    /// it during the drag. The result is returned on every frame of the drag, also when the
+    /// pointer does not move, and the gizmo is drawn at the dragged pose over a dimmed copy
+    /// at the original pose, while the model matrix stays unchanged. The host decides whether
+    /// to commit the final result, which has [`GizmoResult::drag_ended`] set. Disabled by default.
+    pub const fn preview_only(mut self, preview_only: bool) -> Self {
+        self.config.preview_only = preview_only;
+        self
+    }
+
+    /// Time constant in seconds of the exponential smoothing of pointer drags, which damps
+    /// jitter from e.g. hand tremor. The dragged value follows the pointer independently of
+    /// the frame rate, and snapping is applied to the smoothed value. The final result on
+    /// release has the exact value of the pointer. Zero, the default, disables smoothing.
+    pub const fn smoothing(mut self, smoothing: f32) -> Self {
+        self.config.smoothing = smoothing;
+        self
+    }
+
+    /// Whether double-clicking a subgizmo resets the component of the transformation
+    /// it controls, e.g. translation along the X axis is set to zero. Disabled by default.
+    pub const fn double_click_resets(mut self, double_click_resets: bool) -> Self {
+        self.config.double_click_resets = double_click_resets;
+        self
+    }
+
+    /// How pointer movement is mapped to rotation when dragging the arcball.
+    /// [`ArcballBehavior::PointerDelta`] by default.
+    pub const fn arcball_behavior(mut self, arcball_behavior: ArcballBehavior) -> Self {
+        self.config.arcball_behavior = arcball_behavior;
+        self
+    }
+
+    /// Whether two finger touch gestures starting over the gizmo transform it.
+    /// Pinching scales uniformly and twisting rotates around the view axis. Disabled by default.
+    pub const fn touch_gestures(mut self, touch_gestures: bool) -> Self {
+        self.config.touch_gestures = touch_gestures;
+        self
+    }
+
+    /// Multiplier for the rotation applied when dragging the arcball
+    pub const fn arcball_sensitivity(mut self, arcball_sensitivity: f32) -> Self {
+        self.config.arcball_sensitivity = arcball_sensitivity;
+        self
+    }
+
     /// Visual configuration of the gizmo, such as colors and size
     pub const fn visuals(mut self, visuals: GizmoVisuals) -> Self {
         self.config.visuals = visuals;
         self
     }
 
+    /// Overrides the visuals of the subgizmos with the given direction and transform kind.
+    /// Plane subgizmos are identified by the direction of their normal, and rotation
+    /// subgizmos by [`TransformKind::Axis`]. Fields of the override that are not set
+    /// fall back to the visuals given with [`Gizmo::visuals`].
+    pub fn visuals_for(
+        mut self,
+        direction: GizmoDirection,
+        transform_kind: TransformKind,
+        visuals_override: GizmoVisualsOverride,
+    ) -> Self {
+        self.visuals_overrides
+            .push((direction, transform_kind, visuals_override));
+        self
+    }
+
     /// Draw and interact with the gizmo. This consumes the gizmo.
     ///
     /// Returns the result of the interaction, which includes a transformed model matrix.
     /// [`None`] is returned when the gizmo is not active.
-    pub fn interact(mut self, ui: &mut Ui) -> Option<GizmoResult> {
-        self.config.prepare(ui);
+    pub fn interact(self, ui: &mut Ui) -> Option<GizmoResult> {
+        self.interact_detailed(ui).result
+    }
+
+    /// Draw and interact with the gizmo when only a [`Context`] is available,
+    /// e.g. when overlaying the gizmo on a scene rendered with a paint callback.
+    /// This consumes the gizmo.
+    ///
+    /// Input is read from the context and the gizmo is drawn with the given painter,
+    /// on the layer and within the clip rect of the painter.
+    /// The given viewport is used instead of the one set with [`Gizmo::viewport`].
+    pub fn interact_with_painter(
+        self,
+        ctx: &Context,
+        painter: &Painter,
+        viewport: Rect,
+    ) -> Option<GizmoResult> {
+        let mut ui = Ui::new(
+            ctx.clone(),
+            painter.layer_id(),
+            self.id,
+            viewport,
+            painter.clip_rect(),
+        );
+
+        self.viewport(viewport).interact(&mut ui)
+    }
+
+    /// Draw and interact with the gizmo. This consumes the gizmo.
+    ///
+    /// Same as [`Gizmo::interact`], but returns an error if the gizmo cannot be
+    /// used with the given configuration, e.g. when the view projection matrix
+    /// is not invertible. Nothing is drawn in that case.
+    pub fn try_interact(self, ui: &mut Ui) -> Result<Option<GizmoResult>, GizmoError> {
+        self.try_interact_detailed(ui)
+            .map(|interaction| interaction.result)
+    }
+
+    /// Draw and interact with the gizmo. This consumes the gizmo.
+    ///
+    /// Returns detailed information about the interaction, including
+    /// the hovered state of the gizmo when it is not active.
+    pub fn interact_detailed(mut self, ui: &mut Ui) -> GizmoInteraction {
+        self.config.trs_fallback = true;
+        let interaction = self.try_interact_detailed(ui);
+        // Non-finite matrices may be produced for a single frame by e.g. an animation
+        // system, and the viewport has zero area while e.g. its panel is collapsed or
+        // the window is minimized, so the gizmo is just skipped instead of asserting.
+        debug_assert!(
+            interaction
+                .as_ref()
+                .map_or_else(GizmoError::is_transient, |_| true),
+            "invalid gizmo configuration: {}",
+            interaction.as_ref().unwrap_err()
+        );
+
+        interaction.unwrap_or_default()
+    }
+
+    /// Draw and interact with the gizmo. This consumes the gizmo.
+    ///
+    /// Same as [`Gizmo::interact_detailed`], but returns an error if the gizmo
+    /// cannot be used with the given configuration.
+    pub fn try_interact_detailed(mut self, ui: &mut Ui) -> Result<GizmoInteraction, GizmoError> {
+        self.config.prepare(ui)?;
+
+        // Gizmos with the same id in different panels or native viewports
+        // must not share their state, as egui memory is shared between them.
+        self.id = self.id.with(ui.id()).with(ui.ctx().viewport_id());
+
+        if !self.config.visible {
+            return Ok(GizmoInteraction {
+                change_id: GizmoMemory::change_id(ui.ctx(), self.id),
+                debug_info: Some(self.config.debug_info()),
+                ..Default::default()
+            });
+        }
+
+        // Snapping is relative to the start of the drag,
+        // so engaging coarse snapping mid-drag only moves to the nearest coarse step.
+        if let Some(modifiers) = self.config.coarse_snap_modifier {
+            if ui.input(|input| input.modifiers.matches_logically(modifiers)) {
+                self.config.snapping = true;
+                self.config.snap_modes = EnumSet::all();
+                self.config.snap_angle = self.config.coarse_snap_angle;
+                self.config.snap_distance = self.config.coarse_snap_distance;
+            }
+        }
+
+        if let Some(modifiers) = self.config.uniform_scale_modifier {
+            self.config.uniform_scale =
+                ui.input(|input| input.modifiers.matches_logically(modifiers));
+        }
+
+        if self.placement_fn.is_some() {
+            let modifiers = self.config.placement_modifier;
+            self.config.placing = ui.input(|input| input.modifiers.matches_logically(modifiers));
+        }
+
+        let GizmoMemory {
+            mut state,
+            subgizmos,
+        } = GizmoMemory::load(ui.ctx(), self.id);
+        *self.subgizmo_states.borrow_mut() = subgizmos;
+        let was_dragging = state.active_subgizmo_id.is_some();
+        let time = ui.input(|i| i.time);
+
+        // The host does not apply the results of a previewed drag, so the gizmo is shown
+        // and updated at the dragged pose instead of the model matrix.
+        let mut preview_original = None;
+        if let Some(preview_transform) = state
+            .preview_transform
+            .filter(|_| self.config.preview_only && was_dragging)
+        {
+            if let Ok(config) = self.config.with_world_transform(ui, preview_transform) {
+                preview_original = Some(self.config);
+                self.config = config;
+            }
+        }
+
+        // Choose subgizmos based on the gizmo modes
+        let modes = self.config.modes;
+        if modes.contains(GizmoMode::Rotate) {
+            self.add_subgizmos(self.new_rotation());
+        }
+        if modes.contains(GizmoMode::Rotate) || modes.contains(GizmoMode::Arcball) {
+            self.add_subgizmos(self.new_arcball());
+        }
+        if modes.contains(GizmoMode::Translate) {
+            self.add_subgizmos(self.new_translation());
+        }
+        if modes.contains(GizmoMode::Scale) {
+            self.add_subgizmos(self.new_scale());
+        }
+        self.keep_active_subgizmo(&mut state);
+        debug_assert!(
+            self.subgizmos
+                .iter()
+                .enumerate()
+                .all(|(i, subgizmo)| self.subgizmos[..i]
+                    .iter()
+                    .all(|other| other.id() != subgizmo.id())),
+            "subgizmo ids of gizmo {:?} are not unique",
+            self.id
+        );
+
+        let mut result = None;
+        let mut response = None;
+        let mut hovered = None;
+        let mut interaction_point = None;
+        let mut cursor_icon = None;
+        let mut clicked = None;
+        let mut drag_idle = false;
+
+        // A gizmo that is made non-interactable mid-drag ends the drag without a result
+        let interactable = self.config.interactable;
+        if !interactable {
+            state.cancel_drag();
+            state.gesture_active = false;
+        }
+
+        // The drag ends when the drag button is released. This is done before picking,
+        // so that releasing the second click of a double click is detected.
+        // Releasing before the pointer moved beyond the drag threshold is a click.
+        let drag_button = self.config.drag_button;
+        if state.active_subgizmo_id.is_some()
+            && !state.keyboard_grab
+            && !ui.input(|i| i.pointer.button_down(drag_button))
+        {
+            let (released, has_pointer) = ui.input(|i| {
+                (
+                    i.pointer.button_released(drag_button),
+                    i.pointer.has_pointer(),
+                )
+            });
+
+            // The drag delivers a final result on release, so that the host can commit
+            // the transformation even if the pointer did not move on the last frames.
+            if released && state.drag_threshold_exceeded {
+                if let Some(pointer_ray) = self.pointer_ray(ui) {
+                    result = state.active_subgizmo_id.and_then(|id| {
+                        let subgizmo = self
+                            .subgizmos
+                            .iter_mut()
+                            .find(|subgizmo| subgizmo.id() == id)?;
+                        let result = Self::drag_result(subgizmo, ui, &state, pointer_ray, true)?;
+                        Some(GizmoResult {
+                            drag_ended: true,
+                            ..result
+                        })
+                    });
+                }
+            }
+
+            if released && has_pointer && !state.drag_threshold_exceeded {
+                clicked = state.active_subgizmo_id.and_then(|id| {
+                    self.subgizmos
+                        .iter()
+                        .find(|subgizmo| subgizmo.id() == id)
+                        .map(|subgizmo| subgizmo.handle())
+                });
+            }
+
+            // The release may have been missed, e.g. when the window lost the focus
+            // during the drag. The stale drag is discarded, so that the next press
+            // does not continue it.
+            #[cfg(debug_assertions)]
+            if !released {
+                log::debug!("discarding a drag of gizmo {:?} without a release", self.id);
+            }
+
+            state.active_subgizmo_id = None;
+            state.drag_threshold_exceeded = false;
+        }
+
+        // Compact gizmos are only picked once the pointer comes close enough to expand them
+        let expanded = self.is_expanded(ui, &state);
+
+        if let Some(pointer_ray) = self.pointer_ray(ui).filter(|_| interactable) {
+            let viewport = self.config.viewport;
+            // The pointer is registered as a drag on the gizmo only when a subgizmo is focused
+            // or active, and only within the gizmo, so that drags starting elsewhere reach
+            // the widgets behind it, e.g. for rectangle selection. The pointer is included,
+            // since it may focus a subgizmo from outside of its bounds within the pick tolerance.
+            let interact_rect = self
+                .bounding_rect()
+                .union(Rect::from_min_max(
+                    pointer_ray.screen_pos,
+                    pointer_ray.screen_pos,
+                ))
+                .intersect(viewport);
+            let double_click_resets = self.config.double_click_resets;
+            let scroll_nudges = self.config.scroll_nudges;
+            let proportional_radius = self.config.proportional_radius;
+            let model_matrix = self.config.model_matrix;
+            let id = self.id;
+
+            // A press that began outside of the viewport, e.g. on another panel,
+            // neither highlights nor activates subgizmos when the pointer moves in.
+            let pressed_outside = ui.input(|i| {
+                i.pointer
+                    .press_origin()
+                    .is_some_and(|pos| !viewport.contains(pos))
+            });
+
+            // If there is no active subgizmo, find which one of them
+            // is under the mouse pointer, if any. Subgizmos covered by other layers,
+            // such as windows, are ignored. An active subgizmo is kept active
+            // even if the pointer moves over another layer.
+            if state.active_subgizmo_id.is_none()
+                && expanded
+                && self.config.fade_alpha > 0.0
+                && !pressed_outside
+                && !is_occluded(ui, pointer_ray.screen_pos)
+            {
+                if let Some(subgizmo) = self.pick_subgizmo(ui, pointer_ray) {
+                    subgizmo.set_focused(true);
+                    hovered = Some(subgizmo.handle());
+                    interaction_point = subgizmo.interaction_point();
+                    cursor_icon = Some(match subgizmo.mode() {
+                        GizmoMode::Translate => CursorIcon::Move,
+                        GizmoMode::Rotate | GizmoMode::Arcball => CursorIcon::Grab,
+                        GizmoMode::Scale => CursorIcon::ResizeNwSe,
+                    });
+
+                    let interaction = ui.interact(interact_rect, id, Sense::click_and_drag());
+                    let pressed = interaction.is_pointer_button_down_on()
+                        && ui.input(|i| i.pointer.button_pressed(drag_button));
+                    if double_click_resets && interaction.double_clicked_by(drag_button) {
+                        result = Some(GizmoResult {
+                            start_transform: model_matrix.as_mat4().into(),
+                            drag_start_screen: pointer_ray.screen_pos,
+                            current_screen: pointer_ray.screen_pos,
+                            interaction_point: interaction_point
+                                .map(|point| point.as_vec3().into()),
+                            ..subgizmo.reset()
+                        });
+                    } else if pressed {
+                        // The subgizmo is activated on press, so that the transformation
+                        // is measured from the press position, and produces results
+                        // once the pointer has moved beyond the drag threshold.
+                        state.active_subgizmo_id = Some(subgizmo.id());
+                        state.active_mode = Some(subgizmo.mode());
+                        state.start_transform = model_matrix;
+                        state.drag_start_screen = ui
+                            .input(|i| i.pointer.press_origin())
+                            .unwrap_or(pointer_ray.screen_pos);
+                        state.drag_threshold_exceeded = false;
+                        state.drag_start_time = time;
+                        state.drag_frame_count = 0;
+                        state.proportional_radius = proportional_radius.unwrap_or_default();
+                        state.update_input = None;
+                        state.preview_transform = None;
+                    } else if scroll_nudges {
+                        // The scroll is consumed, so that it does not also move e.g. the camera.
+                        // Fractional steps are accumulated for fine grained trackpad scrolling.
+                        state.scroll_remainder += ui.input_mut(|i| {
+                            std::mem::take(&mut i.scroll_delta).y / SCROLL_POINTS_PER_NUDGE
+                        });
+                        let steps = state.scroll_remainder.trunc();
+                        state.scroll_remainder -= steps;
+
+                        if steps != 0.0 {
+                            result = subgizmo.nudge(steps as i32).map(|result| GizmoResult {
+                                start_transform: model_matrix.as_mat4().into(),
+                                drag_start_screen: pointer_ray.screen_pos,
+                                current_screen: pointer_ray.screen_pos,
+                                ..result
+                            });
+                        }
+                    }
+                    response = Some(interaction);
+                } else {
+                    state.scroll_remainder = 0.0;
+                }
+            }
+
+            let active_subgizmo = state.active_subgizmo_id.and_then(|id| {
+                self.subgizmos
+                    .iter_mut()
+                    .find(|subgizmo| subgizmo.id() == id)
+            });
+
+            if let Some(subgizmo) = active_subgizmo {
+                // Only the configured drag button is used for dragging,
+                // so that other buttons can be used for e.g. context menus.
+                if !state.keyboard_grab {
+                    hovered = Some(subgizmo.handle());
+                    if response.is_none() {
+                        response = Some(ui.interact(interact_rect, id, Sense::click_and_drag()));
+                    }
+                    subgizmo.set_focused(true);
+
+                    // Scrolling during the drag adjusts the proportional editing radius
+                    if self.config.proportional_radius.is_some() {
+                        let scroll = ui.input_mut(|i| std::mem::take(&mut i.scroll_delta).y);
+                        state.proportional_radius *= PROPORTIONAL_RADIUS_SCROLL_FACTOR
+                            .powf(scroll / SCROLL_POINTS_PER_NUDGE);
+                    }
+
+                    state.drag_threshold_exceeded |=
+                        pointer_ray.screen_pos.distance(state.drag_start_screen)
+                            > self.config.drag_threshold;
+                }
+
+                if !state.keyboard_grab && state.drag_threshold_exceeded {
+                    // The subgizmo is still updated for drawing, but the result is not
+                    // returned on frames where nothing affecting it has changed.
+                    let update_input = Some(UpdateInput {
+                        pointer: pointer_ray.screen_pos,
+                        modifiers: ui.input(|i| i.modifiers),
+                        snapping: self.config.snaps(subgizmo.mode()),
+                        proportional_radius: state.proportional_radius,
+                    });
+                    // Previewed drags are not applied by the host, which needs the result
+                    // on every frame to show the dragged pose.
+                    drag_idle = state.update_input == update_input && !self.config.preview_only;
+                    state.update_input = update_input;
+
+                    result = Self::drag_result(subgizmo, ui, &state, pointer_ray, false);
+
+                    // The smoothed result keeps moving towards the pointer while it is still
+                    if self.config.smoothing > 0.0 {
+                        drag_idle &= result.map_or(true, |result| {
+                            result.transform_equals(
+                                self.config.scale.as_vec3(),
+                                self.config.rotation.as_quat(),
+                                self.config.translation.as_vec3(),
+                                f32::EPSILON,
+                            )
+                        });
+                    }
+                    interaction_point = subgizmo.interaction_point();
+                    cursor_icon = Some(CursorIcon::Grabbing);
+                }
+            }
+        }
+
+        let (keyboard_result, touch_result) = if interactable {
+            let pointer_ray = self.pointer_ray(ui);
+            (
+                self.interact_keyboard(ui, &mut state, pointer_ray),
+                self.interact_touch(ui, &mut state),
+            )
+        } else {
+            (None, None)
+        };
+        // Never let a non-finite transformation leak out of the gizmo
+        result = result
+            .or(keyboard_result.filter(GizmoResult::is_finite))
+            .or(touch_result.filter(GizmoResult::is_finite));
+
+        // Only snapped transformations give a separate raw value
+        if let Some(result) = &mut result {
+            result.raw_value = result.raw_value.or(result.value);
+        }
+
+        // The radius may have been adjusted during a drag, including the drag ended on this frame
+        let drag_ended = result.is_some_and(|result| result.drag_ended);
+        let proportional_radius = self.config.proportional_radius.map(|radius| {
+            if state.active_subgizmo_id.is_some() && !state.keyboard_grab || drag_ended {
+                state.proportional_radius
+            } else {
+                radius
+            }
+        });
+        if let Some(result) = &mut result {
+            result.proportional_radius = proportional_radius;
+        }
+
+        if let Some(result) = &mut result {
+            result.identity_delta = result.transform_equals(
+                self.config.scale.as_vec3(),
+                self.config.rotation.as_quat(),
+                self.config.translation.as_vec3(),
+                f32::EPSILON,
+            );
+            if !result.identity_delta && !drag_idle {
+                state.change_id += 1;
+            }
+        }
+
+        if let Some(result) = result {
+            self.config.translation = result.translation_f64;
+            self.config.rotation = result.rotation_f64;
+            self.config.scale = result.scale_f64;
+        }
+
+        let dragging = state.active_subgizmo_id.is_some();
+        state.preview_transform = (self.config.preview_only && dragging).then(|| {
+            DMat4::from_scale_rotation_translation(
+                self.config.scale,
+                self.config.rotation,
+                self.config.translation,
+            )
+        });
+
+        // The gizmo transforms in world space, while the host edits the local transformation
+        if let Some(parent_matrix) = self.config.parent_matrix {
+            result = result.map(|result| result.relative_to(parent_matrix));
+        }
+
+        if let Some(cursor_icon) = cursor_icon.filter(|_| self.config.set_cursor) {
+            ui.ctx().set_cursor_icon(cursor_icon);
+        }
+
+        if self.config.show_tooltips && state.active_subgizmo_id.is_none() {
+            let text = hovered.and_then(|handle| match &self.tooltip_fn {
+                Some(tooltip_fn) => tooltip_fn(handle),
+                None => Some(handle.default_tooltip()),
+            });
+            if let (Some(text), Some(hover_response)) = (text, response.take()) {
+                response = Some(hover_response.on_hover_ui_at_pointer(|ui| {
+                    ui.label(text);
+                }));
+            }
+        }
+
+        let expansion = ui.ctx().animate_bool(self.id.with("expansion"), expanded);
+        self.draw_subgizmos(ui, expansion, preview_original.as_ref());
+
+        if let Some(radius) = proportional_radius {
+            self.draw_proportional_radius(ui, radius);
+        }
+
+        if self.config.visuals.show_value {
+            if let Some(result) = result {
+                self.draw_value(ui, result);
+            }
+        }
+
+        let bounding_rect = self.bounding_rect();
+
+        // The statistics of a drag are reported while it is active, and for the last time
+        // on the frame it ends.
+        if dragging {
+            state.drag_frame_count += 1;
+        }
+        let (drag_duration, frame_count) = if dragging || was_dragging {
+            (
+                (time - state.drag_start_time) as f32,
+                state.drag_frame_count,
+            )
+        } else {
+            (0.0, 0)
+        };
+
+        let change_id = state.change_id;
+        GizmoMemory {
+            state,
+            subgizmos: self.subgizmo_states.take(),
+        }
+        .save(ui.ctx(), self.id);
+
+        Ok(GizmoInteraction {
+            result: result.filter(|_| !drag_idle),
+            change_id,
+            active: dragging,
+            response,
+            hovered,
+            clicked,
+            interaction_point: interaction_point.map(|point| point.as_vec3().into()),
+            bounding_rect,
+            world_units_per_pixel: self.config.scale_factor,
+            drag_duration,
+            frame_count,
+            debug_info: Some(self.config.debug_info()),
+        })
+    }
+
+    /// Registers the subgizmos as focusable widgets for keyboard and assistive technology users.
+    /// Tab cycles the focus through the subgizmos and Enter grabs the focused one. The grabbed
+    /// subgizmo is nudged by the snapping steps with the arrow keys, or follows the pointer once
+    /// it moves. Enter commits and Escape cancels the transformation. Assistive technology can
+    /// also nudge the focused subgizmo with the increment and decrement actions.
+    fn interact_keyboard(
+        &mut self,
+        ui: &Ui,
+        state: &mut GizmoState,
+        pointer_ray: Option<Ray>,
+    ) -> Option<GizmoResult> {
+        let mut result = None;
+        let model_matrix = self.config.model_matrix;
+
+        // Widgets are focused in the order they are registered
+        self.subgizmos
+            .sort_by_key(|subgizmo| subgizmo.handle().focus_order());
+
+        for subgizmo in &mut self.subgizmos {
+            let mut grabbed =
+                state.keyboard_grab && state.active_subgizmo_id == Some(subgizmo.id());
+
+            let rect = subgizmo.screen_bounds();
+            if !rect.is_positive() && !grabbed {
+                continue;
+            }
+
+            let response = ui.interact(rect, subgizmo.id(), Sense::focusable_noninteractive());
+
+            let handle = subgizmo.handle();
+            let value = subgizmo.value();
+            response.widget_info(|| {
+                value.map_or_else(
+                    || WidgetInfo::labeled(WidgetType::Slider, handle),
+                    |value| WidgetInfo::slider(value, handle),
+                )
+            });
+
+            let mut steps = 0;
+
+            // Keys are only read while a subgizmo has the keyboard focus, so that keys typed
+            // into e.g. a focused text field are never taken by the gizmo. The pointer works
+            // regardless of the keyboard focus.
+            if response.has_focus() {
+                subgizmo.set_focused(true);
+
+                if grabbed {
+                    // Keep the focus while the arrow keys and escape are used for the transformation
+                    #[cfg(feature = "bench")]
+                    bench::count_memory_access();
+                    ui.memory_mut(|mem| {
+                        mem.set_focus_lock_filter(
+                            response.id,
+                            EventFilter {
+                                horizontal_arrows: true,
+                                vertical_arrows: true,
+                                escape: true,
+                                ..Default::default()
+                            },
+                        );
+                    });
+
+                    steps += ui.input_mut(|input| {
+                        (input.count_and_consume_key(Modifiers::NONE, Key::ArrowUp)
+                            + input.count_and_consume_key(Modifiers::NONE, Key::ArrowRight))
+                            as i32
+                            - (input.count_and_consume_key(Modifiers::NONE, Key::ArrowDown)
+                                + input.count_and_consume_key(Modifiers::NONE, Key::ArrowLeft))
+                                as i32
+                    });
+                }
+
+                let (enter, escape) = ui.input_mut(|input| {
+                    (
+                        input.consume_key(Modifiers::NONE, Key::Enter),
+                        grabbed && input.consume_key(Modifiers::NONE, Key::Escape),
+                    )
+                });
+
+                if enter && !grabbed {
+                    grabbed = true;
+                    state.active_subgizmo_id = Some(subgizmo.id());
+                    state.active_mode = Some(subgizmo.mode());
+                    state.keyboard_grab = true;
+                    state.grab_pointer_moved = false;
+                    state.start_transform = model_matrix;
+                    state.drag_start_time = ui.input(|i| i.time);
+                    state.drag_frame_count = 0;
+                    if let Some(pointer_ray) = pointer_ray {
+                        state.drag_start_screen = pointer_ray.screen_pos;
+                        // Initializes the interaction state of the subgizmo
+                        subgizmo.pick(ui, pointer_ray);
+                    }
+                } else if enter {
+                    grabbed = false;
+                } else if escape {
+                    grabbed = false;
+                    let (scale, rotation, translation) =
+                        state.start_transform.to_scale_rotation_translation();
+                    result = Some(GizmoResult {
+                        mode: handle.mode,
+                        direction: Some(handle.direction),
+                        transform_kind: handle.transform_kind,
+                        ..GizmoResult::from_transform(scale, rotation, translation)
+                    });
+                }
+            } else {
+                // Losing the focus commits the transformation
+                grabbed = false;
+            }
+
+            if state.keyboard_grab && state.active_subgizmo_id == Some(subgizmo.id()) && !grabbed {
+                state.active_subgizmo_id = None;
+                state.keyboard_grab = false;
+            }
+
+            if grabbed {
+                subgizmo.set_active(true);
+
+                state.grab_pointer_moved |= ui.input(|input| input.pointer.delta() != Vec2::ZERO);
+                if let Some(pointer_ray) = pointer_ray.filter(|_| state.grab_pointer_moved) {
+                    result = subgizmo.update(ui, pointer_ray).map(|result| GizmoResult {
+                        drag_start_screen: state.drag_start_screen,
+                        current_screen: pointer_ray.screen_pos,
+                        ..result
+                    });
+                }
+            }
+
+            #[cfg(feature = "accesskit")]
+            {
+                use egui::accesskit::Action;
+                steps += ui.input(|input| {
+                    input.num_accesskit_action_requests(response.id, Action::Increment) as i32
+                        - input.num_accesskit_action_requests(response.id, Action::Decrement) as i32
+                });
+            }
+
+            if steps != 0 {
+                result = subgizmo.nudge(steps).or(result);
+            }
+        }
+
+        result.map(|result| GizmoResult {
+            start_transform: state.start_transform.as_mat4().into(),
+            ..result
+        })
+    }
+
+    /// Two finger pinch gestures scale uniformly and twist gestures rotate around the view axis,
+    /// if the gesture starts over the gizmo. Gestures starting elsewhere are left for the
+    /// application, e.g. for moving the camera.
+    fn interact_touch(&self, ui: &Ui, state: &mut GizmoState) -> Option<GizmoResult> {
+        let modes = self.config.modes;
+        if !self.config.touch_gestures
+            || !modes.contains(GizmoMode::Scale) && !modes.contains(GizmoMode::Rotate)
+        {
+            state.gesture_active = false;
+            return None;
+        }
+
+        let Some(multi_touch) = ui.input(|input| input.multi_touch()) else {
+            state.gesture_active = false;
+            return None;
+        };
+
+        if !state.gesture_active {
+            if state.active_subgizmo_id.is_some()
+                || !self.bounding_rect().contains(multi_touch.start_pos)
+                || is_occluded(ui, multi_touch.start_pos)
+            {
+                return None;
+            }
+
+            state.gesture_active = true;
+            state.gesture_zoom = 1.0;
+            state.gesture_rotation = 0.0;
+            state.gesture_center = multi_touch.start_pos;
+            state.start_transform = self.config.model_matrix;
+            state.drag_start_screen = multi_touch.start_pos;
+        }
+
+        state.gesture_zoom *= multi_touch.zoom_delta as f64;
+        state.gesture_rotation += multi_touch.rotation_delta as f64;
+        state.gesture_center += multi_touch.translation_delta;
+
+        // Fingers held still do not repeat the previous result
+        if multi_touch.zoom_delta == 1.0 && multi_touch.rotation_delta == 0.0 {
+            return None;
+        }
+
+        let (start_scale, start_rotation, _) =
+            state.start_transform.to_scale_rotation_translation();
+
+        let mut factor = state.gesture_zoom;
+        let mut angle = state.gesture_rotation;
+        if self.config.snaps(GizmoMode::Scale) {
+            factor = self
+                .config
+                .scale_snap_mode
+                .snap(factor, self.config.snap_scale);
+        }
+        if self.config.snaps(GizmoMode::Rotate) {
+            angle = round_to_interval(angle, self.config.snap_angle as f64);
+        }
+
+        let scale = if modes.contains(GizmoMode::Scale) {
+            start_scale * factor.max(1e-4)
+        } else {
+            start_scale
+        };
+
+        // Twisting clockwise on the screen rotates clockwise on the screen. The screen shows
+        // the X and Y axes of the camera with both handedness conventions, so that is
+        // around the negative Z axis of the camera.
+        let axis = -self.config.view_forward();
+        let rotation = if modes.contains(GizmoMode::Rotate) {
+            DQuat::from_axis_angle(axis, angle) * start_rotation
+        } else {
+            start_rotation
+        };
+
+        // The result is reported as the more prominent one of the transformations
+        let scaling = !modes.contains(GizmoMode::Rotate)
+            || modes.contains(GizmoMode::Scale) && factor.max(1e-4).ln().abs() >= angle.abs();
+        let mode = if scaling {
+            GizmoMode::Scale
+        } else {
+            GizmoMode::Rotate
+        };
+        let snapped = self.config.snaps(mode);
+
+        Some(GizmoResult {
+            start_transform: state.start_transform.as_mat4().into(),
+            drag_start_screen: state.drag_start_screen,
+            current_screen: state.gesture_center,
+            mode,
+            direction: Some(GizmoDirection::View),
+            transform_kind: Some(if scaling {
+                TransformKind::Plane
+            } else {
+                TransformKind::Axis
+            }),
+            value: Some(if scaling {
+                [factor as f32; 3]
+            } else {
+                (axis * angle).as_vec3().to_array()
+            }),
+            raw_value: Some(if scaling {
+                [state.gesture_zoom as f32; 3]
+            } else {
+                (axis * state.gesture_rotation).as_vec3().to_array()
+            }),
+            snapped,
+            snap_increment: match mode {
+                _ if !snapped => None,
+                GizmoMode::Scale => self
+                    .config
+                    .scale_snap_mode
+                    .increment(self.config.snap_scale),
+                _ => Some(self.config.snap_angle),
+            },
+            ..GizmoResult::from_transform(scale, rotation, self.config.translation)
+        })
+    }
+
+    /// Screen space rectangle enclosing all subgizmos
+    fn bounding_rect(&self) -> Rect {
+        self.subgizmos
+            .iter()
+            .map(|subgizmo| subgizmo.screen_bounds())
+            .fold(Rect::NOTHING, Rect::union)
+    }
+
+    /// Whether the gizmo is shown in full instead of the compact axes.
+    /// See [`GizmoVisuals::compact_when_idle`].
+    fn is_expanded(&self, ui: &Ui, state: &GizmoState) -> bool {
+        let Some(radius) = self.config.visuals.compact_when_idle else {
+            return true;
+        };
+        if state.active_subgizmo_id.is_some() {
+            return true;
+        }
+
+        let origin = world_to_screen(
+            self.config.viewport,
+            self.config.view_projection,
+            self.config.translation,
+        );
+        let pointer = ui.input(|i| i.pointer.hover_pos());
+        origin
+            .zip(pointer)
+            .is_some_and(|(origin, pointer)| origin.distance(pointer) <= radius)
+    }
+
+    /// Draws the subgizmos. The subgizmos of a previewed drag are also drawn dimmed
+    /// at the `original` pose, under the dragged pose.
+    fn draw_subgizmos(&mut self, ui: &mut Ui, expansion: f32, original: Option<&GizmoConfig>) {
+        let dragging = self.subgizmos.iter().any(|subgizmo| subgizmo.is_active());
+        let mut fade_alpha = self.config.fade_alpha;
+        if !self.config.interactable {
+            fade_alpha *= self.config.visuals.disabled_alpha;
+        }
+        if fade_alpha <= 0.0 {
+            return;
+        }
 
-        // Choose subgizmos based on the gizmo mode
-        match self.config.mode {
-            GizmoMode::Rotate => {
-                self.add_subgizmos(self.new_rotation());
-                self.add_subgizmos(self.new_arcball());
+        if expansion < 1.0 {
+            self.draw_compact_axes(ui, (1.0 - expansion) * fade_alpha);
+        }
+        if expansion <= 0.0 {
+            return;
+        }
+
+        // Subgizmos drawn later are drawn on top
+        let draw_order = self.config.draw_order;
+        self.subgizmos
+            .sort_by_key(|subgizmo| draw_order(subgizmo.handle()));
+
+        let dim_inactive_during_drag = self.config.visuals.dim_inactive_during_drag;
+        let opacity = |subgizmo: &dyn SubGizmo| {
+            if !dragging || subgizmo.is_active() {
+                Some(expansion * fade_alpha)
+            } else {
+                dim_inactive_during_drag.map(|alpha| alpha * expansion * fade_alpha)
             }
-            GizmoMode::Translate => self.add_subgizmos(self.new_translation()),
-            GizmoMode::Scale => self.add_subgizmos(self.new_scale()),
         };
 
-        let mut result = None;
-        let mut active_subgizmo = None;
-        let mut state = GizmoState::load(ui.ctx(), self.id);
-
-        if let Some(pointer_ray) = self.pointer_ray(ui) {
-            let viewport = self.config.viewport;
-            let id = self.id;
-
-            // If there is no active subgizmo, find which one of them
-            // is under the mouse pointer, if any.
-            if state.active_subgizmo_id.is_none() {
-                if let Some(subgizmo) = self.pick_subgizmo(ui, pointer_ray) {
-                    subgizmo.set_focused(true);
+        for subgizmo in &mut self.subgizmos {
+            if self.config.force_highlight == Some(subgizmo.handle()) {
+                subgizmo.set_focused(true);
+            }
+        }
 
-                    let interaction = ui.interact(viewport, id, Sense::click_and_drag());
-                    let dragging = interaction.dragged_by(PointerButton::Primary);
-                    if interaction.drag_started() && dragging {
-                        state.active_subgizmo_id = Some(subgizmo.id());
-                    }
+        if let Some(original) = original {
+            for subgizmo in &mut self.subgizmos {
+                if let Some(opacity) = opacity(subgizmo.as_ref()) {
+                    let config = *subgizmo.config_mut();
+                    let (focused, active) = (subgizmo.is_focused(), subgizmo.is_active());
+                    *subgizmo.config_mut() = config.with_pose_of(original);
+                    subgizmo.set_focused(false);
+                    subgizmo.set_active(false);
+                    subgizmo.set_opacity(opacity * PREVIEW_ORIGINAL_ALPHA);
+                    subgizmo.draw(ui);
+                    *subgizmo.config_mut() = config;
+                    subgizmo.set_focused(focused);
+                    subgizmo.set_active(active);
                 }
             }
+        }
 
-            active_subgizmo = state.active_subgizmo_id.and_then(|id| {
-                self.subgizmos
-                    .iter_mut()
-                    .find(|subgizmo| subgizmo.id() == id)
-            });
-
-            if let Some(subgizmo) = active_subgizmo.as_mut() {
-                if ui.input(|i| i.pointer.primary_down()) {
-                    subgizmo.set_active(true);
-                    subgizmo.set_focused(true);
-                    result = subgizmo.update(ui, pointer_ray);
-                } else {
-                    state.active_subgizmo_id = None;
+        // The shadows of all subgizmos are drawn first, so that they are under each subgizmo
+        if let Some(shadow) = self.config.visuals.shadow {
+            for subgizmo in &mut self.subgizmos {
+                if let Some(opacity) = opacity(subgizmo.as_ref()) {
+                    let config = *subgizmo.config_mut();
+                    *subgizmo.config_mut() = config.shadow(shadow);
+                    subgizmo.set_opacity(opacity);
+                    subgizmo.draw(ui);
+                    *subgizmo.config_mut() = config;
                 }
             }
         }
 
-        if let Some((_, result)) = active_subgizmo.zip(result) {
-            self.config.translation = Vec3::from(result.translation).as_dvec3();
-            self.config.rotation = Quat::from(result.rotation).as_dquat();
-            self.config.scale = Vec3::from(result.scale).as_dvec3();
+        for subgizmo in &mut self.subgizmos {
+            if let Some(opacity) = opacity(subgizmo.as_ref()) {
+                subgizmo.set_opacity(opacity);
+                subgizmo.draw(ui);
+            }
         }
+    }
+
+    /// Draws the proportional editing radius as a circle facing the camera
+    fn draw_proportional_radius(&self, ui: &Ui, radius: f32) {
+        let config = &self.config;
+        let rotation = DQuat::from_mat3(&DMat3::from_cols(
+            config.view_up(),
+            -config.view_forward(),
+            -config.view_right(),
+        ));
+
+        let painter = Painter3d::new(
+            ui.painter().clone(),
+            config.view_projection * DMat4::from_rotation_translation(rotation, config.translation),
+            config.viewport,
+        );
 
-        state.save(ui.ctx(), self.id);
+        let color = Color32::WHITE.gamma_multiply(config.visuals.inactive_alpha);
+        painter.circle(
+            radius as f64,
+            (config.visuals.stroke_width / 2.0, color),
+            config.visuals.guide_stroke_style,
+        );
+    }
+
+    /// Draws short axis lines in place of the subgizmos while the gizmo is compact
+    fn draw_compact_axes(&self, ui: &Ui, opacity: f32) {
+        let config = &self.config;
+        let transform = if config.local_space() {
+            DMat4::from_rotation_translation(config.rotation, config.translation)
+        } else {
+            DMat4::from_translation(config.translation)
+        };
 
-        self.draw_subgizmos(ui, &mut state);
+        let painter = Painter3d::new(
+            ui.painter().clone(),
+            config.view_projection * transform,
+            config.viewport,
+        );
 
-        result
+        let length = (config.scale_factor * config.visuals.gizmo_size * COMPACT_AXIS_LENGTH) as f64;
+        let alpha = opacity * config.visuals.inactive_alpha;
+        for (direction, color) in [
+            (GizmoDirection::X, config.visuals.x_color),
+            (GizmoDirection::Y, config.visuals.y_color),
+            (GizmoDirection::Z, config.visuals.z_color),
+        ] {
+            let axis = direction.axis().unwrap_or_default();
+            painter.line_segment(
+                DVec3::ZERO,
+                axis * length,
+                (config.visuals.stroke_width, color.gamma_multiply(alpha)),
+                StrokeStyle::Solid,
+            );
+        }
     }
 
-    fn draw_subgizmos(&mut self, ui: &mut Ui, state: &mut GizmoState) {
-        for subgizmo in &mut self.subgizmos {
-            if state.active_subgizmo_id.is_none() || subgizmo.is_active() {
-                subgizmo.draw(ui);
+    /// Draws the value of the active transformation next to the pointer
+    fn draw_value(&self, ui: &Ui, result: GizmoResult) {
+        let percent_text = match (result.mode, self.config.scale_snap_mode) {
+            (GizmoMode::Scale, _) if self.config.uniform_scale => result.value.map(|[x, ..]| {
+                let precision = self.config.visuals.decimal_places as usize;
+                format!("{:.precision$}%", x * 100.0)
+            }),
+            (GizmoMode::Scale, ScaleSnapMode::Percent(_))
+                if self.config.snaps(GizmoMode::Scale) =>
+            {
+                result.value.map(|value| {
+                    let precision = self.config.visuals.decimal_places as usize;
+                    let [x, y, z] = value.map(|v| v * 100.0);
+                    format!("X: {x:.precision$}%, Y: {y:.precision$}%, Z: {z:.precision$}%")
+                })
             }
+            _ => None,
+        };
+
+        if let Some(text) = percent_text.or_else(|| result.value_text(&self.config.visuals)) {
+            #[allow(deprecated)]
+            let color = self.config.visuals.s_color;
+            ui.painter().text(
+                result.current_screen + Vec2::splat(16.0),
+                Align2::LEFT_TOP,
+                text,
+                FontId::default(),
+                color,
+            );
         }
     }
 
     /// Picks the subgizmo that is closest to the mouse pointer
+    /// Subgizmos with a higher pick priority are preferred over closer ones.
     fn pick_subgizmo(&mut self, ui: &Ui, ray: Ray) -> Option<&mut Box<dyn SubGizmo>> {
+        let pick_priority = self.config.pick_priority;
+
         self.subgizmos
             .iter_mut()
-            .filter_map(|subgizmo| subgizmo.pick(ui, ray).map(|t| (t, subgizmo)))
-            .min_by(|(first, _), (second, _)| first.partial_cmp(second).unwrap_or(Ordering::Equal))
-            .map(|(_, subgizmo)| subgizmo)
+            .filter_map(|subgizmo| {
+                let priority = pick_priority(subgizmo.handle());
+                subgizmo.pick(ui, ray).map(|t| (priority, t, subgizmo))
+            })
+            .min_by(|(first_priority, first, _), (second_priority, second, _)| {
+                second_priority
+                    .cmp(first_priority)
+                    .then(first.partial_cmp(second).unwrap_or(Ordering::Equal))
+            })
+            .map(|(_, _, subgizmo)| subgizmo)
     }
 
     /// Create arcball subgizmo
     fn new_arcball(&self) -> [ArcballSubGizmo; 1] {
-        [ArcballSubGizmo::new(self.id.with("arc"), self.config, ())]
+        [ArcballSubGizmo::new(
+            self.subgizmo_id(GizmoMode::Rotate, GizmoDirection::View, None),
+            self.config,
+            (),
+            self.subgizmo_states.clone(),
+        )]
     }
 
     /// Create subgizmos for rotation
     fn new_rotation(&self) -> [RotationSubGizmo; 4] {
         [
             RotationSubGizmo::new(
-                self.id.with("rx"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Rotate,
+                    GizmoDirection::X,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::X, TransformKind::Axis),
                 RotationParams {
                     direction: GizmoDirection::X,
                 },
+                self.subgizmo_states.clone(),
             ),
             RotationSubGizmo::new(
-                self.id.with("ry"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Rotate,
+                    GizmoDirection::Y,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::Y, TransformKind::Axis),
                 RotationParams {
                     direction: GizmoDirection::Y,
                 },
+                self.subgizmo_states.clone(),
             ),
             RotationSubGizmo::new(
-                self.id.with("rz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Rotate,
+                    GizmoDirection::Z,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::Z, TransformKind::Axis),
                 RotationParams {
                     direction: GizmoDirection::Z,
                 },
+                self.subgizmo_states.clone(),
             ),
             RotationSubGizmo::new(
-                self.id.with("rs"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Rotate,
+                    GizmoDirection::View,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::View, TransformKind::Axis),
                 RotationParams {
                     direction: GizmoDirection::View,
                 },
+                self.subgizmo_states.clone(),
             ),
         ]
     }
@@ -260,130 +1717,347 @@ impl Gizmo {
     fn new_translation(&self) -> [TranslationSubGizmo; 7] {
         [
             TranslationSubGizmo::new(
-                self.id.with("txs"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::View,
+                    Some(TransformKind::Plane),
+                ),
+                self.subgizmo_config(GizmoDirection::View, TransformKind::Plane),
                 TranslationParams {
                     direction: GizmoDirection::View,
                     transform_kind: TransformKind::Plane,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
             TranslationSubGizmo::new(
-                self.id.with("tx"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::X,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::X, TransformKind::Axis),
                 TranslationParams {
                     direction: GizmoDirection::X,
                     transform_kind: TransformKind::Axis,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
             TranslationSubGizmo::new(
-                self.id.with("ty"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::Y,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::Y, TransformKind::Axis),
                 TranslationParams {
                     direction: GizmoDirection::Y,
                     transform_kind: TransformKind::Axis,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
             TranslationSubGizmo::new(
-                self.id.with("tz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::Z,
+                    Some(TransformKind::Axis),
+                ),
+                self.subgizmo_config(GizmoDirection::Z, TransformKind::Axis),
                 TranslationParams {
                     direction: GizmoDirection::Z,
                     transform_kind: TransformKind::Axis,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
             TranslationSubGizmo::new(
-                self.id.with("tyz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::X,
+                    Some(TransformKind::Plane),
+                ),
+                self.subgizmo_config(GizmoDirection::X, TransformKind::Plane),
                 TranslationParams {
                     direction: GizmoDirection::X,
                     transform_kind: TransformKind::Plane,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
             TranslationSubGizmo::new(
-                self.id.with("txz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::Y,
+                    Some(TransformKind::Plane),
+                ),
+                self.subgizmo_config(GizmoDirection::Y, TransformKind::Plane),
                 TranslationParams {
                     direction: GizmoDirection::Y,
                     transform_kind: TransformKind::Plane,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
             TranslationSubGizmo::new(
-                self.id.with("txy"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Translate,
+                    GizmoDirection::Z,
+                    Some(TransformKind::Plane),
+                ),
+                self.subgizmo_config(GizmoDirection::Z, TransformKind::Plane),
                 TranslationParams {
                     direction: GizmoDirection::Z,
                     transform_kind: TransformKind::Plane,
+                    snap_fn: self.snap_fn.clone(),
+                    placement_fn: self.placement_fn.clone(),
                 },
+                self.subgizmo_states.clone(),
             ),
         ]
     }
 
     /// Create subgizmos for scale
     fn new_scale(&self) -> [ScaleSubGizmo; 7] {
+        // Scale only works in local space
+        let scale_config = |direction, transform_kind| GizmoConfig {
+            orientation: GizmoOrientation::Local,
+            ..self.subgizmo_config(direction, transform_kind)
+        };
+
         [
             ScaleSubGizmo::new(
-                self.id.with("txs"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::View,
+                    Some(TransformKind::Plane),
+                ),
+                scale_config(GizmoDirection::View, TransformKind::Plane),
                 ScaleParams {
                     direction: GizmoDirection::View,
                     transform_kind: TransformKind::Plane,
                 },
+                self.subgizmo_states.clone(),
             ),
             ScaleSubGizmo::new(
-                self.id.with("sx"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::X,
+                    Some(TransformKind::Axis),
+                ),
+                scale_config(GizmoDirection::X, TransformKind::Axis),
                 ScaleParams {
                     direction: GizmoDirection::X,
                     transform_kind: TransformKind::Axis,
                 },
+                self.subgizmo_states.clone(),
             ),
             ScaleSubGizmo::new(
-                self.id.with("sy"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::Y,
+                    Some(TransformKind::Axis),
+                ),
+                scale_config(GizmoDirection::Y, TransformKind::Axis),
                 ScaleParams {
                     direction: GizmoDirection::Y,
                     transform_kind: TransformKind::Axis,
                 },
+                self.subgizmo_states.clone(),
             ),
             ScaleSubGizmo::new(
-                self.id.with("sz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::Z,
+                    Some(TransformKind::Axis),
+                ),
+                scale_config(GizmoDirection::Z, TransformKind::Axis),
                 ScaleParams {
                     direction: GizmoDirection::Z,
                     transform_kind: TransformKind::Axis,
                 },
+                self.subgizmo_states.clone(),
             ),
             ScaleSubGizmo::new(
-                self.id.with("syz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::X,
+                    Some(TransformKind::Plane),
+                ),
+                scale_config(GizmoDirection::X, TransformKind::Plane),
                 ScaleParams {
                     direction: GizmoDirection::X,
                     transform_kind: TransformKind::Plane,
                 },
+                self.subgizmo_states.clone(),
             ),
             ScaleSubGizmo::new(
-                self.id.with("sxz"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::Y,
+                    Some(TransformKind::Plane),
+                ),
+                scale_config(GizmoDirection::Y, TransformKind::Plane),
                 ScaleParams {
                     direction: GizmoDirection::Y,
                     transform_kind: TransformKind::Plane,
                 },
+                self.subgizmo_states.clone(),
             ),
             ScaleSubGizmo::new(
-                self.id.with("sxy"),
-                self.config,
+                self.subgizmo_id(
+                    GizmoMode::Scale,
+                    GizmoDirection::Z,
+                    Some(TransformKind::Plane),
+                ),
+                scale_config(GizmoDirection::Z, TransformKind::Plane),
                 ScaleParams {
                     direction: GizmoDirection::Z,
                     transform_kind: TransformKind::Plane,
                 },
+                self.subgizmo_states.clone(),
             ),
         ]
     }
 
+    /// Identifier of a subgizmo, unique within the gizmo and stable between frames
+    fn subgizmo_id(
+        &self,
+        mode: GizmoMode,
+        direction: GizmoDirection,
+        transform_kind: Option<TransformKind>,
+    ) -> Id {
+        self.id.with(GizmoHandle {
+            mode,
+            direction,
+            transform_kind,
+        })
+    }
+
+    /// Configuration for the subgizmo with the given direction and transform kind,
+    /// with the visuals overrides of that subgizmo applied
+    fn subgizmo_config(
+        &self,
+        direction: GizmoDirection,
+        transform_kind: TransformKind,
+    ) -> GizmoConfig {
+        let mut config = self.config;
+
+        for (_, _, visuals_override) in self
+            .visuals_overrides
+            .iter()
+            .filter(|(d, k, _)| *d == direction && *k == transform_kind)
+        {
+            config.visuals = visuals_override.apply(config.visuals, direction);
+        }
+        config.focus_distance = config.compute_focus_distance();
+
+        config
+    }
+
+    /// Updates the dragged subgizmo with the pointer ray. The `exact` value of the pointer
+    /// is used instead of the smoothed one, e.g. for the final result on release.
+    fn drag_result(
+        subgizmo: &mut Box<dyn SubGizmo>,
+        ui: &Ui,
+        state: &GizmoState,
+        pointer_ray: Ray,
+        exact: bool,
+    ) -> Option<GizmoResult> {
+        let config = subgizmo.config_mut();
+        config.smoothing_factor = if config.smoothing > 0.0 && !exact {
+            let dt = ui.input(|i| i.stable_dt) as f64;
+            1.0 - (-dt / config.smoothing as f64).exp()
+        } else {
+            1.0
+        };
+
+        subgizmo.set_active(true);
+        subgizmo
+            .update(ui, pointer_ray)
+            .map(|result| GizmoResult {
+                start_transform: state.start_transform.as_mat4().into(),
+                drag_start_screen: state.drag_start_screen,
+                current_screen: pointer_ray.screen_pos,
+                interaction_point: subgizmo
+                    .interaction_point()
+                    .map(|point| point.as_vec3().into()),
+                ..result
+            })
+            // Never let a non-finite transformation leak out of the gizmo
+            .filter(GizmoResult::is_finite)
+    }
+
+    /// Keeps the active subgizmo when the modes change during a drag, e.g. with a hotkey
+    /// of the application, so that the drag continues until it is released. The drag is
+    /// cancelled if the active subgizmo can not be found.
+    fn keep_active_subgizmo(&mut self, state: &mut GizmoState) {
+        let Some((id, mode)) = state.active_subgizmo_id.zip(state.active_mode) else {
+            return;
+        };
+
+        match self.subgizmos.iter().find(|subgizmo| subgizmo.id() == id) {
+            Some(subgizmo) if subgizmo.mode() == mode => return,
+            // A subgizmo of another mode must not continue with the state of the drag
+            Some(_) => {
+                state.cancel_drag();
+                return;
+            }
+            None => {}
+        }
+
+        let count = self.subgizmos.len();
+        match mode {
+            GizmoMode::Rotate | GizmoMode::Arcball => {
+                self.add_subgizmos(self.new_rotation());
+                self.add_subgizmos(self.new_arcball());
+            }
+            GizmoMode::Translate => self.add_subgizmos(self.new_translation()),
+            GizmoMode::Scale => self.add_subgizmos(self.new_scale()),
+        }
+
+        let active = self
+            .subgizmos
+            .split_off(count)
+            .into_iter()
+            .find(|subgizmo| subgizmo.id() == id && subgizmo.mode() == mode);
+        match active {
+            Some(subgizmo) => self.subgizmos.push(subgizmo),
+            None => state.cancel_drag(),
+        }
+    }
+
     /// Add given subgizmos to this gizmo
     fn add_subgizmos<T: SubGizmo, const N: usize>(&mut self, subgizmos: [T; N]) {
         for subgizmo in subgizmos {
-            self.subgizmos.push(Box::new(subgizmo));
+            if self.is_enabled(subgizmo.handle()) {
+                self.subgizmos.push(Box::new(subgizmo));
+            }
+        }
+    }
+
+    /// Whether the subgizmo with the given handle should be created
+    fn is_enabled(&self, handle: GizmoHandle) -> bool {
+        // Only the plane and its normal axis are shown with the planar preset
+        if let Some((plane, _)) = self.config.planar {
+            return handle.transform_kind.is_some() && handle.direction == plane.normal();
+        }
+
+        match (
+            handle.transform_kind,
+            GizmoPlane::from_normal(handle.direction),
+        ) {
+            (Some(TransformKind::Plane), Some(plane)) => self.config.planes.contains(plane),
+            // The arcball has no transform kind, and is not affected by show_view_handles
+            (Some(_), None) => self.config.show_view_handles,
+            _ => true,
         }
     }
 
@@ -397,6 +2071,10 @@ impl Gizmo {
 
         let direction = target.sub(origin).normalize();
 
+        if !origin.is_finite() || !direction.is_finite() {
+            return None;
+        }
+
         Some(Ray {
             screen_pos,
             origin,
@@ -405,8 +2083,11 @@ impl Gizmo {
     }
 }
 
-/// Result of an active transformation
-#[derive(Debug, Copy, Clone)]
+/// Result of an active transformation. The default result is the identity transformation.
+///
+/// Results are compared exactly with [`PartialEq`], including the full precision
+/// transformation. See [`GizmoResult::approx_eq`] for comparing only the transformation.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct GizmoResult {
     /// Updated scale
     pub scale: mint::Vector3<f32>,
@@ -414,13 +2095,130 @@ pub struct GizmoResult {
     pub rotation: mint::Quaternion<f32>,
     /// Updated translation
     pub translation: mint::Vector3<f32>,
-    /// Mode of the active subgizmo
+    /// Mode of the active subgizmo. Results of the arcball have [`GizmoMode::Arcball`]
+    /// if it is one of the modes of the gizmo, and [`GizmoMode::Rotate`] otherwise.
     pub mode: GizmoMode,
+    /// Direction of the active subgizmo
+    pub direction: Option<GizmoDirection>,
+    /// Kind of transformation done by the active subgizmo.
+    /// [`None`] for free rotation with the arcball.
+    pub transform_kind: Option<TransformKind>,
     /// Total scale, rotation or translation of the current gizmo activation, depending on mode
     pub value: Option<[f32; 3]>,
+    /// Same as [`GizmoResult::value`], but before snapping.
+    /// Equal to the value when snapping is disabled.
+    pub raw_value: Option<[f32; 3]>,
+    /// Transformation matrix at the start of the current gizmo activation
+    pub start_transform: mint::ColumnMatrix4<f32>,
+    /// Screen position of the pointer at the start of the current gizmo activation
+    pub drag_start_screen: Pos2,
+    /// Current screen position of the pointer
+    pub current_screen: Pos2,
+    /// World space point where the pointer ray intersects the active subgizmo
+    pub interaction_point: Option<mint::Vector3<f32>>,
+    /// Whether the result was produced by double-clicking a subgizmo to reset
+    /// a component of the transformation, rather than by dragging.
+    /// See [`Gizmo::double_click_resets`].
+    pub reset: bool,
+    /// Proportional editing radius in world units, which may have been adjusted
+    /// by scrolling during the drag. See [`Gizmo::proportional_radius`].
+    pub proportional_radius: Option<f32>,
+    /// Whether the value was snapped, with either the snapping enabled with
+    /// [`Gizmo::snapping`] or the coarse snapping of [`Gizmo::coarse_snap_modifier`]
+    pub snapped: bool,
+    /// Increment the value was snapped to: the snap distance, angle in radians, or
+    /// scale factor step. [`None`] if the value was not snapped, or was snapped with
+    /// [`Gizmo::snap_fn`], [`ScaleSnapMode::Percent`] or [`ScaleSnapMode::PowersOf`],
+    /// which have no fixed increment.
+    pub snap_increment: Option<f32>,
+    /// Whether the result is the last one of a drag, produced on the frame the drag
+    /// button is released. The transformation can be committed e.g. to an undo history.
+    pub drag_ended: bool,
+    // Whether the transformation is the same as the one given to the gizmo
+    identity_delta: bool,
+    // Full precision transformation, which the public fields are converted from
+    scale_f64: DVec3,
+    rotation_f64: DQuat,
+    translation_f64: DVec3,
+}
+
+impl Default for GizmoResult {
+    fn default() -> Self {
+        Self {
+            scale: Vec3::ONE.into(),
+            rotation: Quat::IDENTITY.into(),
+            translation: Vec3::ZERO.into(),
+            mode: GizmoMode::Rotate,
+            direction: None,
+            transform_kind: None,
+            value: None,
+            raw_value: None,
+            start_transform: Mat4::IDENTITY.into(),
+            drag_start_screen: Pos2::ZERO,
+            current_screen: Pos2::ZERO,
+            interaction_point: None,
+            reset: false,
+            proportional_radius: None,
+            snapped: false,
+            snap_increment: None,
+            drag_ended: false,
+            identity_delta: false,
+            scale_f64: DVec3::ONE,
+            rotation_f64: DQuat::IDENTITY,
+            translation_f64: DVec3::ZERO,
+        }
+    }
 }
 
 impl GizmoResult {
+    /// Result with the given transformation
+    pub(crate) fn from_transform(scale: DVec3, rotation: DQuat, translation: DVec3) -> Self {
+        Self {
+            scale: scale.as_vec3().into(),
+            rotation: rotation.as_quat().into(),
+            translation: translation.as_vec3().into(),
+            scale_f64: scale,
+            rotation_f64: rotation,
+            translation_f64: translation,
+            ..Default::default()
+        }
+    }
+
+    /// Updated scale in full precision. Changes to [`GizmoResult::scale`] are not reflected here.
+    pub fn scale_f64(&self) -> mint::Vector3<f64> {
+        self.scale_f64.into()
+    }
+
+    /// Updated rotation in full precision. Changes to [`GizmoResult::rotation`] are not reflected here.
+    pub fn rotation_f64(&self) -> mint::Quaternion<f64> {
+        self.rotation_f64.into()
+    }
+
+    /// Updated translation in full precision. Changes to [`GizmoResult::translation`] are not reflected here.
+    pub fn translation_f64(&self) -> mint::Vector3<f64> {
+        self.translation_f64.into()
+    }
+
+    /// Updated transformation matrix in full precision, in column major order.
+    pub fn transform_f64(&self) -> mint::ColumnMatrix4<f64> {
+        DMat4::from_scale_rotation_translation(
+            self.scale_f64,
+            self.rotation_f64,
+            self.translation_f64,
+        )
+        .into()
+    }
+
+    /// Subgizmo that produced the result. [`None`] if the result
+    /// was not produced by a subgizmo.
+    pub fn handle(&self) -> Option<GizmoHandle> {
+        self.direction.map(|direction| GizmoHandle {
+            mode: self.mode,
+            direction,
+            transform_kind: self.transform_kind,
+        })
+    }
+
     /// Updated transformation matrix in column major order.
     pub fn transform(&self) -> mint::ColumnMatrix4<f32> {
         Mat4::from_scale_rotation_translation(
@@ -430,9 +2228,394 @@ impl GizmoResult {
         )
         .into()
     }
+
+    /// Whether the result leaves the transformation given with [`Gizmo::model_matrix`]
+    /// unchanged within `f32` precision, e.g. when a drag is snapped to the same step
+    /// or a nudge is clamped.
+    pub fn is_identity_delta(&self) -> bool {
+        self.identity_delta
+    }
+
+    /// Updated transformation relative to the parent given with [`Gizmo::parent_matrix`],
+    /// in column major order. The same as [`GizmoResult::transform`], which is relative to
+    /// the parent as well, and the world transformation if there is no parent.
+    pub fn local_transform(&self) -> mint::ColumnMatrix4<f32> {
+        self.transform()
+    }
+
+    /// Result with the transformations relative to the given parent transformation
+    fn relative_to(self, parent_matrix: DMat4) -> Self {
+        let inverse_parent = parent_matrix.inverse();
+        let (scale, rotation, translation) = (inverse_parent
+            * DMat4::from_scale_rotation_translation(
+                self.scale_f64,
+                self.rotation_f64,
+                self.translation_f64,
+            ))
+        .to_scale_rotation_translation();
+        let local = Self::from_transform(scale, rotation, translation);
+        let start_transform = inverse_parent.as_mat4() * Mat4::from(self.start_transform);
+
+        Self {
+            scale: local.scale,
+            rotation: local.rotation,
+            translation: local.translation,
+            scale_f64: local.scale_f64,
+            rotation_f64: local.rotation_f64,
+            translation_f64: local.translation_f64,
+            start_transform: start_transform.into(),
+            ..self
+        }
+    }
+
+    /// Whether the scale, rotation and translation of the results are equal within
+    /// `epsilon`, e.g. for comparing with the result of the previous frame.
+    /// The other fields are ignored.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.transform_equals(
+            other.scale.into(),
+            other.rotation.into(),
+            other.translation.into(),
+            epsilon,
+        )
+    }
+
+    /// Whether the transformation of the result equals the given one within `epsilon`
+    fn transform_equals(
+        &self,
+        scale: Vec3,
+        rotation: Quat,
+        translation: Vec3,
+        epsilon: f32,
+    ) -> bool {
+        Vec3::from(self.scale).abs_diff_eq(scale, epsilon)
+            && Vec3::from(self.translation).abs_diff_eq(translation, epsilon)
+            // Opposite quaternions are the same rotation
+            && (Quat::from(self.rotation).abs_diff_eq(rotation, epsilon)
+                || Quat::from(self.rotation).abs_diff_eq(-rotation, epsilon))
+    }
+
+    /// Whether the transformation of the result is finite
+    fn is_finite(&self) -> bool {
+        self.scale_f64.is_finite()
+            && self.rotation_f64.is_finite()
+            && self.translation_f64.is_finite()
+            && Vec3::from(self.scale).is_finite()
+            && Quat::from(self.rotation).is_finite()
+            && Vec3::from(self.translation).is_finite()
+    }
+
+    /// Formats the value of the result, using the units configured in the given visuals
+    pub fn value_text(&self, visuals: &GizmoVisuals) -> Option<String> {
+        let value = Vec3::from(self.value?);
+        let precision = visuals.decimal_places as usize;
+
+        let text = match self.mode {
+            GizmoMode::Rotate | GizmoMode::Arcball => {
+                let angle = value.length();
+                match visuals.angle_unit {
+                    AngleUnit::Degrees => format!("{:.precision$}°", angle.to_degrees()),
+                    AngleUnit::Radians => format!("{angle:.precision$} rad"),
+                }
+            }
+            GizmoMode::Translate => {
+                let suffix = visuals.distance_suffix.unwrap_or_default();
+                format!(
+                    "dX: {:.precision$}{suffix}, dY: {:.precision$}{suffix}, dZ: {:.precision$}{suffix}",
+                    value.x, value.y, value.z
+                )
+            }
+            GizmoMode::Scale => format!(
+                "dX: {:.precision$}, dY: {:.precision$}, dZ: {:.precision$}",
+                value.x, value.y, value.z
+            ),
+        };
+
+        Some(text)
+    }
+}
+
+/// Error returned when the gizmo cannot be used with the given configuration
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GizmoError {
+    /// The viewport has zero or negative width or height
+    DegenerateViewport,
+    /// The model matrix contains NaN or infinite values
+    NonFiniteModelMatrix,
+    /// The view or projection matrix contains NaN or infinite values
+    NonFiniteViewProjection,
+    /// The projection matrix is the identity matrix, which is the default
+    /// when the projection matrix has not been set
+    MissingProjection,
+    /// The view projection matrix cannot be inverted, e.g. because
+    /// the projection matrix has not been set correctly
+    NonInvertibleViewProjection,
+    /// The parent matrix cannot be inverted, e.g. because the parent has zero scale
+    NonInvertibleParentMatrix,
+    /// The model matrix has shear or perspective terms, and cannot be decomposed into
+    /// translation, rotation and scale. See [`is_trs`]. Only returned by
+    /// [`Gizmo::try_interact`] and [`Gizmo::try_interact_detailed`], while the other
+    /// methods show the gizmo with the closest translation, rotation and scale.
+    NonTrsModelMatrix,
+}
+
+impl GizmoError {
+    /// Whether the error is caused by non-finite input values or a collapsed viewport,
+    /// which are expected to recover on the following frames
+    const fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::DegenerateViewport
+                | Self::NonFiniteModelMatrix
+                | Self::NonFiniteViewProjection
+                | Self::NonInvertibleParentMatrix
+        )
+    }
+}
+
+impl std::fmt::Display for GizmoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::DegenerateViewport => "viewport has zero area",
+            Self::NonFiniteModelMatrix => "model matrix is not finite",
+            Self::NonFiniteViewProjection => "view or projection matrix is not finite",
+            Self::MissingProjection => "projection matrix has not been set",
+            Self::NonInvertibleViewProjection => "view projection matrix is not invertible",
+            Self::NonInvertibleParentMatrix => "parent matrix is not invertible",
+            Self::NonTrsModelMatrix => "model matrix has shear or perspective terms",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for GizmoError {}
+
+/// Information passed to the function given to [`Gizmo::snap_fn`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SnapQuery {
+    /// World space position of the gizmo before snapping
+    pub position: mint::Vector3<f32>,
+    /// Direction of the active subgizmo
+    pub direction: GizmoDirection,
+    /// Whether the active subgizmo translates along an axis or on a plane
+    pub transform_kind: TransformKind,
+    /// World space origin of the pointer ray
+    pub ray_origin: mint::Vector3<f32>,
+    /// World space direction of the pointer ray
+    pub ray_direction: mint::Vector3<f32>,
+}
+
+pub(crate) type SnapFn = dyn Fn(SnapQuery) -> Option<mint::Vector3<f32>>;
+
+/// Information passed to the function given to [`Gizmo::placement_fn`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlacementQuery {
+    /// World space position of the gizmo at the start of the drag
+    pub start_position: mint::Vector3<f32>,
+    /// World space origin of the pointer ray
+    pub ray_origin: mint::Vector3<f32>,
+    /// World space direction of the pointer ray
+    pub ray_direction: mint::Vector3<f32>,
+}
+
+pub(crate) type PlacementFn =
+    dyn Fn(PlacementQuery) -> Option<(mint::Vector3<f32>, mint::Vector3<f32>)>;
+
+type TooltipFn = dyn Fn(GizmoHandle) -> Option<String>;
+
+/// Detailed information about the interaction with the gizmo during a frame
+#[derive(Debug, Clone)]
+pub struct GizmoInteraction {
+    /// Result of an active transformation. [`None`] when the gizmo is not active,
+    /// or when the pointer has not moved during a drag. A final result is given on
+    /// the frame the drag ends, see [`GizmoResult::drag_ended`].
+    pub result: Option<GizmoResult>,
+    /// Incremented on every frame where [`GizmoInteraction::result`] changes the
+    /// transformation, so that data derived from the transformation can be cached
+    /// with it as the key. It is kept in egui memory with the state of the gizmo.
+    pub change_id: u64,
+    /// Whether a subgizmo is dragged or grabbed with the keyboard. The drag may
+    /// continue on frames without a [`GizmoInteraction::result`], when nothing
+    /// affecting the transformation has changed.
+    pub active: bool,
+    /// Response of the gizmo widget. It exists when a subgizmo is hovered or active,
+    /// and can be used for e.g. showing a context menu with [`egui::Response::context_menu`].
+    pub response: Option<Response>,
+    /// Subgizmo that is hovered or active, if any
+    pub hovered: Option<GizmoHandle>,
+    /// Subgizmo that was clicked with the drag button this frame, that is pressed and
+    /// released without moving the pointer beyond the drag threshold.
+    pub clicked: Option<GizmoHandle>,
+    /// World space point where the pointer ray intersects the focused
+    /// or active subgizmo, if any.
+    pub interaction_point: Option<mint::Vector3<f32>>,
+    /// Screen space rectangle enclosing all handles of the gizmo.
+    /// [`Rect::NOTHING`] when the gizmo is not shown.
+    pub bounding_rect: Rect,
+    /// Size of a screen pixel in world units at the gizmo origin, for drawing
+    /// overlays that match the size of the gizmo. Zero when the gizmo is not shown.
+    pub world_units_per_pixel: f32,
+    /// Time in seconds since the active drag started, or the total duration
+    /// of the drag on the frame it ends. Zero when there is no drag.
+    pub drag_duration: f32,
+    /// Number of frames the active drag has lasted, including the current one,
+    /// or the total number of frames of the drag on the frame it ends.
+    /// Zero when there is no drag.
+    pub frame_count: u32,
+    /// Configuration of the gizmo as resolved for this frame.
+    /// [`None`] when the gizmo could not be used with the given configuration.
+    pub debug_info: Option<GizmoDebugInfo>,
+}
+
+/// Configuration of the gizmo as resolved for a frame, for diagnosing
+/// e.g. a gizmo that is not shown where the object is
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GizmoDebugInfo {
+    /// Viewport of the gizmo. Defaults to the clip rect of the ui.
+    pub viewport: Rect,
+    /// Whether a left handed projection was detected
+    pub left_handed: bool,
+    /// Whether an orthographic projection was detected
+    pub orthographic: bool,
+    /// Size of a screen pixel in world units at the gizmo origin
+    pub scale_factor: f32,
+    /// Screen position of the gizmo origin, if it projects to the screen
+    pub gizmo_screen_pos: Option<Pos2>,
+    /// Whether the gizmo was not shown, because its origin does not project
+    /// to the screen, e.g. when it is behind the camera.
+    pub culled: bool,
+    /// Whether the model matrix had shear or perspective terms, which were ignored
+    pub non_trs_model_matrix: bool,
+}
+
+impl Default for GizmoInteraction {
+    fn default() -> Self {
+        Self {
+            result: None,
+            change_id: 0,
+            active: false,
+            response: None,
+            hovered: None,
+            clicked: None,
+            interaction_point: None,
+            bounding_rect: Rect::NOTHING,
+            world_units_per_pixel: 0.0,
+            drag_duration: 0.0,
+            frame_count: 0,
+            debug_info: None,
+        }
+    }
+}
+
+/// Identifies a single subgizmo, such as the X axis arrow of the translation gizmo
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct GizmoHandle {
+    /// Mode of the gizmo the subgizmo belongs to
+    pub mode: GizmoMode,
+    /// Direction of the subgizmo
+    pub direction: GizmoDirection,
+    /// Kind of transformation done by the subgizmo.
+    /// [`None`] for free rotation with the arcball.
+    pub transform_kind: Option<TransformKind>,
+}
+
+impl std::fmt::Display for GizmoHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self.mode {
+            GizmoMode::Rotate => "Rotate",
+            GizmoMode::Translate => "Translate",
+            GizmoMode::Scale => "Scale",
+            GizmoMode::Arcball => "Arcball",
+        };
+
+        match (self.transform_kind, self.direction) {
+            (None, _) => f.write_str("Arcball"),
+            (Some(TransformKind::Axis), GizmoDirection::View) => write!(f, "{mode} view"),
+            (Some(TransformKind::Axis), direction) => write!(f, "{mode} {direction:?}"),
+            (Some(TransformKind::Plane), GizmoDirection::View) => {
+                write!(f, "{mode} view plane")
+            }
+            (Some(TransformKind::Plane), direction) => {
+                let plane = GizmoPlane::from_normal(direction).map_or("", GizmoPlane::name);
+                write!(f, "{mode} {plane} plane")
+            }
+        }
+    }
+}
+
+impl GizmoHandle {
+    /// Default draw order of the subgizmos. Planes are drawn first, then rotation
+    /// rings and the arcball, then axis arrows and lastly the view aligned
+    /// center handles of translation and scale.
+    pub const fn default_draw_order(self) -> i32 {
+        match (self.mode, self.transform_kind, self.direction) {
+            (GizmoMode::Rotate | GizmoMode::Arcball, _, _) | (_, None, _) => 1,
+            (_, Some(TransformKind::Plane), GizmoDirection::View) => 3,
+            (_, Some(TransformKind::Plane), _) => 0,
+            (_, Some(TransformKind::Axis), _) => 2,
+        }
+    }
+
+    /// Default tooltip text describing what dragging the subgizmo does,
+    /// such as "Move along X" or "Rotate around view axis"
+    pub fn default_tooltip(self) -> String {
+        let plane = GizmoPlane::from_normal(self.direction).map_or("", GizmoPlane::name);
+
+        match (self.mode, self.transform_kind, self.direction) {
+            (GizmoMode::Arcball, _, _) | (_, None, _) => "Rotate freely".to_owned(),
+            (GizmoMode::Rotate, _, GizmoDirection::View) => "Rotate around view axis".to_owned(),
+            (GizmoMode::Rotate, _, direction) => format!("Rotate around {direction:?}"),
+            (GizmoMode::Translate, Some(TransformKind::Plane), GizmoDirection::View) => {
+                "Move in view plane".to_owned()
+            }
+            (GizmoMode::Translate, Some(TransformKind::Plane), _) => {
+                format!("Move in {plane} plane")
+            }
+            (GizmoMode::Translate, Some(TransformKind::Axis), direction) => {
+                format!("Move along {direction:?}")
+            }
+            (GizmoMode::Scale, Some(TransformKind::Plane), GizmoDirection::View) => {
+                "Scale uniformly".to_owned()
+            }
+            (GizmoMode::Scale, Some(TransformKind::Plane), _) => format!("Scale in {plane} plane"),
+            (GizmoMode::Scale, Some(TransformKind::Axis), direction) => {
+                format!("Scale along {direction:?}")
+            }
+        }
+    }
+
+    /// Default pick priority of the subgizmos, which is the same for all of them
+    pub const fn default_pick_priority(self) -> i32 {
+        0
+    }
+
+    /// Order in which the subgizmos are focused with the keyboard:
+    /// translation, rotation and scale, each axes first, then planes.
+    const fn focus_order(self) -> (u8, u8, u8) {
+        let mode = match self.mode {
+            GizmoMode::Translate => 0,
+            GizmoMode::Rotate | GizmoMode::Arcball => 1,
+            GizmoMode::Scale => 2,
+        };
+        let kind = match self.transform_kind {
+            Some(TransformKind::Axis) => 0,
+            Some(TransformKind::Plane) => 1,
+            None => 2,
+        };
+        let direction = match self.direction {
+            GizmoDirection::X => 0,
+            GizmoDirection::Y => 1,
+            GizmoDirection::Z => 2,
+            GizmoDirection::View => 3,
+        };
+
+        (mode, kind, direction)
+    }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Mode of the gizmo, which decides the kind of transformation it does.
+/// Several modes can be enabled at the same time with [`Gizmo::modes`].
+#[derive(Debug, Hash, EnumSetType)]
 pub enum GizmoMode {
     /// Only rotation
     Rotate,
@@ -440,9 +2623,12 @@ pub enum GizmoMode {
     Translate,
     /// Only scale
     Scale,
+    /// Only free rotation with the arcball
+    Arcball,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Orientation of the transformation axes of the gizmo
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum GizmoOrientation {
     /// Transformation axes are aligned to world space. Rotation of the
     /// gizmo does not change.
@@ -452,20 +2638,230 @@ pub enum GizmoOrientation {
     Local,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-pub enum GizmoDirection {
-    /// Gizmo points in the X-direction
-    X,
-    /// Gizmo points in the Y-direction
-    Y,
-    /// Gizmo points in the Z-direction
-    Z,
-    /// Gizmo points in the view direction
-    View,
+/// Direction of a subgizmo. For plane subgizmos, this is the normal of the plane.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GizmoDirection {
+    /// Gizmo points in the X-direction
+    X,
+    /// Gizmo points in the Y-direction
+    Y,
+    /// Gizmo points in the Z-direction
+    Z,
+    /// Gizmo points in the view direction
+    View,
+}
+
+impl GizmoDirection {
+    /// Unit vector of the direction, rotated by the given rotation of the gizmo
+    /// with [`GizmoOrientation::Local`]. [`None`] for the view direction, which
+    /// depends on the camera.
+    pub fn to_vec3(
+        self,
+        orientation: GizmoOrientation,
+        rotation: impl Into<mint::Quaternion<f32>>,
+    ) -> Option<mint::Vector3<f32>> {
+        let axis = self.axis()?.as_vec3();
+        Some(match orientation {
+            GizmoOrientation::Global => axis.into(),
+            GizmoOrientation::Local => (Quat::from(rotation.into()) * axis).into(),
+        })
+    }
+
+    /// Direction of the given vector, if it is exactly one of the unit X, Y or Z vectors
+    pub fn from_vec3(vec: impl Into<mint::Vector3<f32>>) -> Option<Self> {
+        let vec = Vec3::from(vec.into());
+        [Self::X, Self::Y, Self::Z]
+            .into_iter()
+            .find(|direction| direction.axis() == Some(vec.as_dvec3()))
+    }
+
+    /// Unit vector of the direction in the local space of the gizmo.
+    /// [`None`] for the view direction.
+    pub(crate) const fn axis(self) -> Option<DVec3> {
+        match self {
+            Self::X => Some(DVec3::X),
+            Self::Y => Some(DVec3::Y),
+            Self::Z => Some(DVec3::Z),
+            Self::View => None,
+        }
+    }
+}
+
+/// Plane of the plane subgizmos used for translation and scale
+#[derive(Debug, Hash, EnumSetType)]
+pub enum GizmoPlane {
+    /// Plane spanned by the X and Y axes
+    XY,
+    /// Plane spanned by the X and Z axes
+    XZ,
+    /// Plane spanned by the Y and Z axes
+    YZ,
+}
+
+impl GizmoPlane {
+    /// Plane with the given normal direction. [`None`] for the view direction.
+    const fn from_normal(direction: GizmoDirection) -> Option<Self> {
+        match direction {
+            GizmoDirection::X => Some(Self::YZ),
+            GizmoDirection::Y => Some(Self::XZ),
+            GizmoDirection::Z => Some(Self::XY),
+            GizmoDirection::View => None,
+        }
+    }
+
+    /// Direction of the normal of the plane
+    const fn normal(self) -> GizmoDirection {
+        match self {
+            Self::XY => GizmoDirection::Z,
+            Self::XZ => GizmoDirection::Y,
+            Self::YZ => GizmoDirection::X,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::XY => "XY",
+            Self::XZ => "XZ",
+            Self::YZ => "YZ",
+        }
+    }
+}
+
+/// Placement of the plane subgizmos relative to the gizmo origin
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PlanePlacement {
+    /// Planes are placed in the quadrant between the two positive axes
+    Quadrant,
+    /// Planes are centered on the gizmo origin
+    Centered,
+}
+
+/// Kind of transformation done by a subgizmo
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TransformKind {
+    /// Transformation along or around a single axis
+    Axis,
+    /// Transformation on a plane
+    Plane,
+}
+
+/// How the scale is snapped when snapping is enabled
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ScaleSnapMode {
+    /// The scale factor of the drag is snapped to multiples of the snap scale
+    /// given with [`Gizmo::snap_scale`], e.g. 0.9, 1.0, 1.1
+    Additive,
+    /// The scale factor of the drag is snapped to steps growing by the given percentage,
+    /// e.g. 90.9%, 100%, 110%, 121% with a value of `10.0`. Percentages of zero or less
+    /// disable the snapping.
+    Percent(f32),
+    /// The scale factor of the drag is snapped to powers of the given base,
+    /// e.g. 0.5, 1, 2, 4 with a value of `2.0`. A base below one snaps like its inverse,
+    /// and a base of one, zero or less disables the snapping.
+    PowersOf(f32),
+}
+
+impl ScaleSnapMode {
+    /// Snaps the scale factor of a transformation
+    fn snap(self, factor: f64, snap_scale: f32) -> f64 {
+        match self {
+            Self::Additive => round_to_interval(factor, snap_scale as f64),
+            Self::Percent(percent) => snap_to_powers(factor, 1.0 + percent as f64 / 100.0),
+            Self::PowersOf(base) => snap_to_powers(factor, base as f64),
+        }
+    }
+
+    /// Step between the snapped scale factors, if they are evenly spaced
+    fn increment(self, snap_scale: f32) -> Option<f32> {
+        match self {
+            Self::Additive => Some(snap_scale),
+            Self::Percent(_) | Self::PowersOf(_) => None,
+        }
+    }
+
+    /// Ratio between the snapped scale factors, if they grow geometrically
+    fn step_factor(self) -> Option<f64> {
+        match self {
+            Self::Additive => None,
+            Self::Percent(percent) => powers_base(1.0 + percent as f64 / 100.0),
+            Self::PowersOf(base) => powers_base(base as f64),
+        }
+    }
+}
+
+/// Snaps a scale factor to the nearest power of the base in log space
+fn snap_to_powers(factor: f64, base: f64) -> f64 {
+    powers_base(base).map_or(factor, |base| base.powf(factor.max(1e-4).log(base).round()))
+}
+
+/// Base above one with the same powers as the given base, if there is one
+fn powers_base(base: f64) -> Option<f64> {
+    // The powers of a base below one are the same as those of its inverse
+    let base = if base < 1.0 { base.recip() } else { base };
+    (base.is_finite() && base > 1.0 + f64::EPSILON).then_some(base)
+}
+
+/// Point of the object that stays in place when scaling
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ScaleOrigin {
+    /// The object is scaled around the gizmo origin
+    Center,
+    /// The negative side of the bounds given with [`Gizmo::local_bounds`]
+    /// stays in place, e.g. the -X face when scaling along the X axis
+    NegativeSide,
+    /// The positive side of the bounds given with [`Gizmo::local_bounds`]
+    /// stays in place, e.g. the +X face when scaling along the X axis
+    PositiveSide,
+}
+
+impl ScaleOrigin {
+    /// Point in the local space of the object that stays in place when scaling.
+    /// Without bounds, the object is scaled around the gizmo origin.
+    fn local_point(self, bounds: Option<(DVec3, DVec3)>) -> DVec3 {
+        match (self, bounds) {
+            (Self::NegativeSide, Some((min, _))) => min,
+            (Self::PositiveSide, Some((_, max))) => max,
+            _ => DVec3::ZERO,
+        }
+    }
+}
+
+/// How pointer movement is mapped to rotation when dragging the arcball
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ArcballBehavior {
+    /// Pointer movement between frames rotates the arcball,
+    /// scaled by [`Gizmo::arcball_sensitivity`]
+    PointerDelta,
+    /// The arcball acts as a virtual trackball, so that the point of its
+    /// surface grabbed at the start of the drag follows the pointer
+    Trackball,
+}
+
+/// Style of the lines drawn by the gizmo
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StrokeStyle {
+    /// Continuous line
+    Solid,
+    /// Dashes of the given length separated by gaps of the given length, in points
+    Dashed { length: f32, gap: f32 },
+    /// Dots separated by the given spacing, in points
+    Dotted { spacing: f32 },
+}
+
+/// Unit used for displaying angles
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AngleUnit {
+    /// Angles are displayed in degrees
+    Degrees,
+    /// Angles are displayed in radians
+    Radians,
 }
 
-/// Controls the visual style of the gizmo
-#[derive(Debug, Copy, Clone)]
+/// Controls the visual style of the gizmo.
+///
+/// Individual fields can be customized with the `with_*` methods, e.g.
+/// `GizmoVisuals::default().with_stroke_width(2.0).with_gizmo_size(100.0)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct GizmoVisuals {
     /// Color of the x axis
     pub x_color: Color32,
@@ -473,8 +2869,18 @@ pub struct GizmoVisuals {
     pub y_color: Color32,
     /// Color of the z axis
     pub z_color: Color32,
-    /// Color of the forward axis
+    /// Color of the view aligned handles and the displayed values.
+    /// It is used for the handles unless overridden by
+    /// `view_ring_color` or `view_plane_color`.
+    #[deprecated(note = "use `view_ring_color` and `view_plane_color` instead")]
     pub s_color: Color32,
+    /// Color of the view aligned rotation ring
+    pub view_ring_color: Option<Color32>,
+    /// Color of the view aligned translation and scale handles
+    pub view_plane_color: Option<Color32>,
+    /// Colors of the XY, XZ and YZ planes. By default, the planes use
+    /// the color of the axis perpendicular to them.
+    pub plane_colors: Option<[Color32; 3]>,
     /// Alpha of the gizmo color when inactive
     pub inactive_alpha: f32,
     /// Alpha of the gizmo color when highlighted/active
@@ -485,21 +2891,500 @@ pub struct GizmoVisuals {
     pub stroke_width: f32,
     /// Gizmo size in pixels
     pub gizmo_size: f32,
+    /// Distance from the gizmo origin to the start of the axis arrows,
+    /// relative to the gizmo size
+    pub axis_start_offset: f32,
+    /// Distance from the gizmo origin to the arrowheads of the axis arrows,
+    /// relative to the gizmo size
+    pub axis_length: f32,
+    /// Length of the translation arrowheads relative to the gizmo size.
+    /// By default, the length depends on the stroke width.
+    pub arrow_head_size: Option<f32>,
+    /// Size of the square handles at the ends of the scale arrows in pixels
+    pub scale_handle_size: f32,
+    /// Whether to show shorter and dimmer lines in the negative direction of the
+    /// translation and scale axes. The lines can be dragged like the arrows.
+    pub show_negative_axes: bool,
+    /// Radius of the arcball relative to the gizmo size
+    pub arcball_radius: f32,
+    /// Whether to show a grid of the snapping targets when translating with snapping enabled
+    pub show_snap_grid: bool,
+    /// Number of cells shown along each axis of the snap grid
+    pub snap_grid_size: u32,
+    /// Whether to show tick marks of the snapping targets along the axis
+    /// when translating along an axis with snapping enabled
+    pub show_snap_ruler: bool,
+    /// Style of the guide lines, such as the lines of the snap grid and the circle
+    /// of the proportional radius
+    pub guide_stroke_style: StrokeStyle,
+    /// Whether to show the value of the active transformation next to the pointer
+    pub show_value: bool,
+    /// Unit of the displayed rotation values
+    pub angle_unit: AngleUnit,
+    /// Suffix of the displayed translation values, such as `"m"`
+    pub distance_suffix: Option<&'static str>,
+    /// Number of decimal places in the displayed values
+    pub decimal_places: u8,
+    /// Alpha multiplier of the other subgizmos while one of them is dragged.
+    /// By default, the other subgizmos are hidden during a drag.
+    pub dim_inactive_during_drag: Option<f32>,
+    /// Distance in pixels from the gizmo origin within which the pointer expands the gizmo.
+    /// Farther away, only short axis lines are drawn while no subgizmo is dragged.
+    /// By default, the gizmo is always expanded.
+    pub compact_when_idle: Option<f32>,
+    /// Near and far distances in world units between the camera and the gizmo origin,
+    /// outside of which the whole gizmo fades out. Closer than the near distance, the
+    /// gizmo fades out linearly until it is hidden at half of the near distance. Farther
+    /// than the far distance, it fades out until it is hidden at twice the far distance.
+    /// Use [`f32::INFINITY`] as the far distance to only fade out close to the camera.
+    /// Hidden gizmos cannot be picked. By default, the gizmo does not fade.
+    pub fade_distance: Option<(f32, f32)>,
+    /// Alpha multiplier of the whole gizmo when it is not interactable.
+    /// See [`Gizmo::interactable`].
+    pub disabled_alpha: f32,
+    /// Screen space offset, color and blur of a shadow drawn under the subgizmos, which
+    /// improves their readability against bright backgrounds. The blur widens the strokes
+    /// of the shadow by the given number of points. The shadow is not picked.
+    /// By default, no shadow is drawn.
+    pub shadow: Option<(Vec2, Color32, f32)>,
+    /// Alpha of the fill of the highlighted and active plane subgizmos.
+    /// By default, `highlight_alpha` is used.
+    pub plane_highlight_fill_alpha: Option<f32>,
+    /// Function giving the color of each subgizmo, consulted before the axis, view and
+    /// plane colors. Highlighting and the alpha multipliers are applied on top of it.
+    /// Colors given with [`Gizmo::visuals_for`] take precedence over it.
+    pub color_for: Option<fn(GizmoHandle) -> Color32>,
 }
 
 impl Default for GizmoVisuals {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             x_color: Color32::from_rgb(255, 50, 0),
             y_color: Color32::from_rgb(50, 255, 0),
             z_color: Color32::from_rgb(0, 50, 255),
             s_color: Color32::from_rgb(255, 255, 255),
+            view_ring_color: None,
+            view_plane_color: None,
+            plane_colors: None,
             inactive_alpha: 0.5,
             highlight_alpha: 0.9,
             highlight_color: None,
             stroke_width: 4.0,
             gizmo_size: 75.0,
+            axis_start_offset: 0.2,
+            axis_length: 1.0,
+            arrow_head_size: None,
+            scale_handle_size: 10.0,
+            show_negative_axes: false,
+            arcball_radius: 1.0,
+            show_snap_grid: false,
+            snap_grid_size: 10,
+            show_snap_ruler: false,
+            guide_stroke_style: StrokeStyle::Solid,
+            show_value: false,
+            angle_unit: AngleUnit::Degrees,
+            distance_suffix: None,
+            decimal_places: 2,
+            dim_inactive_during_drag: None,
+            compact_when_idle: None,
+            fade_distance: None,
+            disabled_alpha: 0.4,
+            shadow: None,
+            plane_highlight_fill_alpha: None,
+            color_for: None,
+        }
+    }
+}
+
+impl GizmoVisuals {
+    /// Color of the x axis
+    pub const fn with_x_color(mut self, x_color: Color32) -> Self {
+        self.x_color = x_color;
+        self
+    }
+
+    /// Color of the y axis
+    pub const fn with_y_color(mut self, y_color: Color32) -> Self {
+        self.y_color = y_color;
+        self
+    }
+
+    /// Color of the z axis
+    pub const fn with_z_color(mut self, z_color: Color32) -> Self {
+        self.z_color = z_color;
+        self
+    }
+
+    /// Color of the view aligned rotation ring and translation and scale handles
+    #[deprecated(note = "use `with_view_ring_color` and `with_view_plane_color` instead")]
+    pub const fn with_s_color(self, s_color: Color32) -> Self {
+        self.with_view_ring_color(s_color)
+            .with_view_plane_color(s_color)
+    }
+
+    /// Color of the view aligned rotation ring
+    pub const fn with_view_ring_color(mut self, view_ring_color: Color32) -> Self {
+        self.view_ring_color = Some(view_ring_color);
+        self
+    }
+
+    /// Color of the view aligned translation and scale handles
+    pub const fn with_view_plane_color(mut self, view_plane_color: Color32) -> Self {
+        self.view_plane_color = Some(view_plane_color);
+        self
+    }
+
+    /// Colors of the XY, XZ and YZ planes
+    pub const fn with_plane_colors(mut self, plane_colors: [Color32; 3]) -> Self {
+        self.plane_colors = Some(plane_colors);
+        self
+    }
+
+    /// Alpha of the gizmo color when inactive
+    pub const fn with_inactive_alpha(mut self, inactive_alpha: f32) -> Self {
+        self.inactive_alpha = inactive_alpha;
+        self
+    }
+
+    /// Alpha of the gizmo color when highlighted/active
+    pub const fn with_highlight_alpha(mut self, highlight_alpha: f32) -> Self {
+        self.highlight_alpha = highlight_alpha;
+        self
+    }
+
+    /// Color to use for highlighted and active axes
+    pub const fn with_highlight_color(mut self, highlight_color: Color32) -> Self {
+        self.highlight_color = Some(highlight_color);
+        self
+    }
+
+    /// Width (thickness) of the gizmo strokes
+    pub const fn with_stroke_width(mut self, stroke_width: f32) -> Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    /// Gizmo size in pixels
+    pub const fn with_gizmo_size(mut self, gizmo_size: f32) -> Self {
+        self.gizmo_size = gizmo_size;
+        self
+    }
+
+    /// Distance from the gizmo origin to the start of the axis arrows,
+    /// relative to the gizmo size
+    pub const fn with_axis_start_offset(mut self, axis_start_offset: f32) -> Self {
+        self.axis_start_offset = axis_start_offset;
+        self
+    }
+
+    /// Distance from the gizmo origin to the arrowheads of the axis arrows,
+    /// relative to the gizmo size
+    pub const fn with_axis_length(mut self, axis_length: f32) -> Self {
+        self.axis_length = axis_length;
+        self
+    }
+
+    /// Length of the translation arrowheads relative to the gizmo size
+    pub const fn with_arrow_head_size(mut self, arrow_head_size: f32) -> Self {
+        self.arrow_head_size = Some(arrow_head_size);
+        self
+    }
+
+    /// Size of the square handles at the ends of the scale arrows in pixels
+    pub const fn with_scale_handle_size(mut self, scale_handle_size: f32) -> Self {
+        self.scale_handle_size = scale_handle_size;
+        self
+    }
+
+    /// Whether to show shorter and dimmer lines in the negative direction of the
+    /// translation and scale axes
+    pub const fn with_show_negative_axes(mut self, show_negative_axes: bool) -> Self {
+        self.show_negative_axes = show_negative_axes;
+        self
+    }
+
+    /// Radius of the arcball relative to the gizmo size
+    pub const fn with_arcball_radius(mut self, arcball_radius: f32) -> Self {
+        self.arcball_radius = arcball_radius;
+        self
+    }
+
+    /// Whether to show a grid of the snapping targets when translating with snapping enabled
+    pub const fn with_show_snap_grid(mut self, show_snap_grid: bool) -> Self {
+        self.show_snap_grid = show_snap_grid;
+        self
+    }
+
+    /// Number of cells shown along each axis of the snap grid
+    pub const fn with_snap_grid_size(mut self, snap_grid_size: u32) -> Self {
+        self.snap_grid_size = snap_grid_size;
+        self
+    }
+
+    /// Whether to show tick marks of the snapping targets along the axis
+    /// when translating along an axis with snapping enabled
+    pub const fn with_show_snap_ruler(mut self, show_snap_ruler: bool) -> Self {
+        self.show_snap_ruler = show_snap_ruler;
+        self
+    }
+
+    /// Style of the guide lines, such as the lines of the snap grid
+    pub const fn with_guide_stroke_style(mut self, guide_stroke_style: StrokeStyle) -> Self {
+        self.guide_stroke_style = guide_stroke_style;
+        self
+    }
+
+    /// Whether to show the value of the active transformation next to the pointer
+    pub const fn with_show_value(mut self, show_value: bool) -> Self {
+        self.show_value = show_value;
+        self
+    }
+
+    /// Unit of the displayed rotation values
+    pub const fn with_angle_unit(mut self, angle_unit: AngleUnit) -> Self {
+        self.angle_unit = angle_unit;
+        self
+    }
+
+    /// Suffix of the displayed translation values, such as `"m"`
+    pub const fn with_distance_suffix(mut self, distance_suffix: &'static str) -> Self {
+        self.distance_suffix = Some(distance_suffix);
+        self
+    }
+
+    /// Number of decimal places in the displayed values
+    pub const fn with_decimal_places(mut self, decimal_places: u8) -> Self {
+        self.decimal_places = decimal_places;
+        self
+    }
+
+    /// Alpha multiplier of the other subgizmos while one of them is dragged
+    pub const fn with_dim_inactive_during_drag(mut self, dim_inactive_during_drag: f32) -> Self {
+        self.dim_inactive_during_drag = Some(dim_inactive_during_drag);
+        self
+    }
+
+    /// Distance in pixels from the gizmo origin within which the pointer expands the gizmo
+    pub const fn with_compact_when_idle(mut self, compact_when_idle: f32) -> Self {
+        self.compact_when_idle = Some(compact_when_idle);
+        self
+    }
+
+    /// Near and far distances from the camera outside of which the gizmo fades out
+    pub const fn with_fade_distance(mut self, near: f32, far: f32) -> Self {
+        self.fade_distance = Some((near, far));
+        self
+    }
+
+    /// Alpha multiplier of the whole gizmo when it is not interactable
+    pub const fn with_disabled_alpha(mut self, disabled_alpha: f32) -> Self {
+        self.disabled_alpha = disabled_alpha;
+        self
+    }
+
+    /// Screen space offset, color and blur of a shadow drawn under the subgizmos
+    pub const fn with_shadow(mut self, offset: Vec2, color: Color32, blur: f32) -> Self {
+        self.shadow = Some((offset, color, blur));
+        self
+    }
+
+    /// Alpha of the fill of the highlighted and active plane subgizmos
+    pub const fn with_plane_highlight_fill_alpha(
+        mut self,
+        plane_highlight_fill_alpha: f32,
+    ) -> Self {
+        self.plane_highlight_fill_alpha = Some(plane_highlight_fill_alpha);
+        self
+    }
+
+    /// Function giving the color of each subgizmo
+    pub const fn with_color_for(mut self, color_for: fn(GizmoHandle) -> Color32) -> Self {
+        self.color_for = Some(color_for);
+        self
+    }
+}
+
+/// Overrides the visual style of specific subgizmos. See [`Gizmo::visuals_for`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct GizmoVisualsOverride {
+    /// Color of the subgizmo
+    pub color: Option<Color32>,
+    /// Width (thickness) of the subgizmo strokes
+    pub stroke_width: Option<f32>,
+    /// Multiplier for the alpha of the subgizmo color when inactive
+    pub inactive_alpha: Option<f32>,
+    /// Multiplier for the alpha of the subgizmo color when highlighted/active,
+    /// including the fill alpha of highlighted planes
+    pub highlight_alpha: Option<f32>,
+}
+
+impl GizmoVisualsOverride {
+    /// Applies the override on top of the given visuals
+    fn apply(&self, mut visuals: GizmoVisuals, direction: GizmoDirection) -> GizmoVisuals {
+        if let Some(color) = self.color {
+            visuals.color_for = None;
+            match direction {
+                GizmoDirection::X => visuals.x_color = color,
+                GizmoDirection::Y => visuals.y_color = color,
+                GizmoDirection::Z => visuals.z_color = color,
+                GizmoDirection::View => {
+                    visuals.view_ring_color = Some(color);
+                    visuals.view_plane_color = Some(color);
+                }
+            }
+            // The plane colors take precedence over the axis colors
+            if let Some(plane_colors) = &mut visuals.plane_colors {
+                match direction {
+                    GizmoDirection::X => plane_colors[2] = color,
+                    GizmoDirection::Y => plane_colors[1] = color,
+                    GizmoDirection::Z => plane_colors[0] = color,
+                    GizmoDirection::View => {}
+                }
+            }
+        }
+        if let Some(stroke_width) = self.stroke_width {
+            visuals.stroke_width = stroke_width;
+        }
+        if let Some(inactive_alpha) = self.inactive_alpha {
+            visuals.inactive_alpha *= inactive_alpha;
         }
+        if let Some(highlight_alpha) = self.highlight_alpha {
+            visuals.highlight_alpha *= highlight_alpha;
+            visuals.plane_highlight_fill_alpha = visuals
+                .plane_highlight_fill_alpha
+                .map(|alpha| alpha * highlight_alpha);
+        }
+        visuals
+    }
+}
+
+/// Camera dependent configuration that can be shared by several gizmos in the same
+/// viewport. It is built once per frame and given to each gizmo with
+/// [`Gizmo::with_camera`], so that the values depending only on the camera
+/// are calculated once instead of for every gizmo.
+#[derive(Debug, Copy, Clone)]
+pub struct GizmoCameraConfig {
+    view_matrix: DMat4,
+    projection_matrix: DMat4,
+    flip_y: bool,
+    viewport: Rect,
+    visuals: GizmoVisuals,
+    snapping: bool,
+    snap_angle: f32,
+    snap_distance: f32,
+    snap_scale: f32,
+    view_projection: DMat4,
+    left_handed: bool,
+}
+
+impl GizmoCameraConfig {
+    /// Creates a camera configuration from the view and projection matrices of the
+    /// viewport camera, in column major order.
+    pub fn new(
+        view_matrix: impl Into<mint::ColumnMatrix4<f32>>,
+        projection_matrix: impl Into<mint::ColumnMatrix4<f32>>,
+    ) -> Self {
+        let view_matrix = Mat4::from(view_matrix.into()).as_dmat4();
+        let projection_matrix = Mat4::from(projection_matrix.into()).as_dmat4();
+
+        Self {
+            view_matrix,
+            projection_matrix,
+            flip_y: false,
+            viewport: Rect::NOTHING,
+            visuals: GizmoVisuals::default(),
+            snapping: false,
+            snap_angle: DEFAULT_SNAP_ANGLE,
+            snap_distance: DEFAULT_SNAP_DISTANCE,
+            snap_scale: DEFAULT_SNAP_SCALE,
+            view_projection: projection_matrix * view_matrix,
+            left_handed: is_left_handed(projection_matrix),
+        }
+    }
+
+    /// Bounds of the viewport in pixels
+    pub const fn viewport(mut self, viewport: Rect) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Whether the projection matrix flips the Y axis, as is common with Vulkan and wgpu.
+    /// Normalized device coordinates are then expected to have Y pointing down.
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self.view_projection = y_up_projection(self.projection_matrix, flip_y) * self.view_matrix;
+        self
+    }
+
+    /// Visual configuration of the gizmos, such as colors and size
+    pub const fn visuals(mut self, visuals: GizmoVisuals) -> Self {
+        self.visuals = visuals;
+        self
+    }
+
+    /// Whether snapping is enabled
+    pub const fn snapping(mut self, snapping: bool) -> Self {
+        self.snapping = snapping;
+        self
+    }
+
+    /// Snap angle to use for rotation when snapping is enabled
+    pub const fn snap_angle(mut self, snap_angle: f32) -> Self {
+        self.snap_angle = snap_angle;
+        self
+    }
+
+    /// Snap distance to use for translation when snapping is enabled
+    pub const fn snap_distance(mut self, snap_distance: f32) -> Self {
+        self.snap_distance = snap_distance;
+        self
+    }
+
+    /// Snap distance to use for scaling when snapping is enabled
+    pub const fn snap_scale(mut self, snap_scale: f32) -> Self {
+        self.snap_scale = snap_scale;
+        self
+    }
+}
+
+/// Converts a projection matrix with flipped Y axis to one with Y pointing up,
+/// which is what the screen space calculations expect.
+fn y_up_projection(projection_matrix: DMat4, flip_y: bool) -> DMat4 {
+    if flip_y {
+        DMat4::from_scale(DVec3::new(1.0, -1.0, 1.0)) * projection_matrix
+    } else {
+        projection_matrix
+    }
+}
+
+/// Whether the given screen position is covered by another layer that is
+/// drawn on top of the layer of the ui, e.g. an overlapping window.
+fn is_occluded(ui: &Ui, pos: Pos2) -> bool {
+    let layer_id = ui.layer_id();
+    let Some(top_layer_id) = ui.ctx().layer_id_at(pos) else {
+        return false;
+    };
+
+    match top_layer_id.order.cmp(&layer_id.order) {
+        Ordering::Less => false,
+        Ordering::Greater => true,
+        // Layers that are not areas are painted above the areas of the same order
+        Ordering::Equal => ui.ctx().memory(|mem| {
+            #[cfg(feature = "bench")]
+            bench::count_memory_access();
+            let mut layer_ids = mem.layer_ids().skip_while(|&id| id != layer_id);
+            layer_ids.next().is_some() && layer_ids.any(|id| id == top_layer_id)
+        }),
+    }
+}
+
+/// Whether the given projection matrix is for a left handed coordinate system
+fn is_left_handed(projection_matrix: DMat4) -> bool {
+    if projection_matrix.z_axis.w == 0.0 {
+        projection_matrix.z_axis.z > 0.0
+    } else {
+        projection_matrix.z_axis.w > 0.0
     }
 }
 
@@ -507,26 +3392,89 @@ impl Default for GizmoVisuals {
 pub(crate) struct GizmoConfig {
     pub view_matrix: DMat4,
     pub projection_matrix: DMat4,
+    /// Whether the projection matrix flips the Y axis
+    pub flip_y: bool,
+    /// World transformation of the gizmo. Relative to the parent matrix until prepared.
     pub model_matrix: DMat4,
+    pub parent_matrix: Option<DMat4>,
+    /// Whether a model matrix that is not a pure TRS is replaced with the closest one,
+    /// instead of being an error
+    pub trs_fallback: bool,
+    /// Whether the model matrix was replaced, because it was not a pure TRS
+    pub non_trs: bool,
     pub viewport: Rect,
-    pub mode: GizmoMode,
+    pub modes: EnumSet<GizmoMode>,
     pub orientation: GizmoOrientation,
+    pub planes: EnumSet<GizmoPlane>,
+    /// Plane and its size relative to the gizmo size, with [`Gizmo::planar_translation`]
+    pub planar: Option<(GizmoPlane, f32)>,
+    pub plane_placement: PlanePlacement,
+    pub show_view_handles: bool,
+    pub draw_order: fn(GizmoHandle) -> i32,
+    pub pick_priority: fn(GizmoHandle) -> i32,
     pub snapping: bool,
+    /// Modes that are snapped when snapping is enabled
+    pub snap_modes: EnumSet<GizmoMode>,
     pub snap_angle: f32,
     pub snap_distance: f32,
     pub snap_scale: f32,
+    pub scale_snap_mode: ScaleSnapMode,
+    pub uniform_scale_modifier: Option<Modifiers>,
+    /// Whether the uniform scale modifier is held
+    pub uniform_scale: bool,
+    pub placement_modifier: Modifiers,
+    /// Whether the placement modifier is held
+    pub placing: bool,
+    pub align_to_normal: bool,
+    pub scale_origin: ScaleOrigin,
+    pub local_bounds: Option<(DVec3, DVec3)>,
+    pub coarse_snap_modifier: Option<Modifiers>,
+    pub coarse_snap_angle: f32,
+    pub coarse_snap_distance: f32,
+    pub arcball_sensitivity: f32,
+    pub arcball_behavior: ArcballBehavior,
+    pub touch_gestures: bool,
+    pub set_cursor: bool,
+    pub show_tooltips: bool,
+    pub scroll_nudges: bool,
+    pub proportional_radius: Option<f32>,
+    pub force_highlight: Option<GizmoHandle>,
+    /// Viewing direction and up vector overriding those of the camera
+    pub view_override: Option<(DVec3, DVec3)>,
+    pub drag_button: PointerButton,
+    pub double_click_resets: bool,
+    pub interactable: bool,
+    pub preview_only: bool,
+    pub smoothing: f32,
+    /// Weight of the exact value in the smoothed value of the dragged subgizmo on the
+    /// current frame. One without smoothing.
+    pub smoothing_factor: f64,
+    pub drag_threshold: f32,
     pub visuals: GizmoVisuals,
     //----------------------------------//
     pub rotation: DQuat,
     pub translation: DVec3,
     pub scale: DVec3,
+    /// Whether rotation, translation and scale were given directly instead of the model matrix
+    pub decomposed: bool,
     pub view_projection: DMat4,
     pub mvp: DMat4,
+    /// Orthonormal basis of the camera in world space, with columns right, up and forward
+    pub view_axes: DMat3,
     pub gizmo_view_forward: DVec3,
+    /// Whether the gizmo origin projects to the screen
+    pub visible: bool,
+    /// Alpha multiplier of the whole gizmo from [`GizmoVisuals::fade_distance`]
+    pub fade_alpha: f32,
     pub scale_factor: f32,
     /// How close the mouse pointer needs to be to a subgizmo before it is focused
     pub focus_distance: f32,
+    pub pick_tolerance: Option<f32>,
+    /// Pick tolerance of the egui style, used unless one is given
+    pub style_pick_tolerance: f32,
     pub left_handed: bool,
+    /// Whether view projection and handedness were precalculated by [`GizmoCameraConfig`]
+    pub camera_prepared: bool,
 }
 
 impl Default for GizmoConfig {
@@ -534,25 +3482,70 @@ impl Default for GizmoConfig {
         Self {
             view_matrix: DMat4::IDENTITY,
             projection_matrix: DMat4::IDENTITY,
+            flip_y: false,
             model_matrix: DMat4::IDENTITY,
+            parent_matrix: None,
+            trs_fallback: false,
+            non_trs: false,
             viewport: Rect::NOTHING,
-            mode: GizmoMode::Rotate,
+            modes: EnumSet::only(GizmoMode::Rotate),
             orientation: GizmoOrientation::Global,
+            planes: EnumSet::all(),
+            planar: None,
+            plane_placement: PlanePlacement::Quadrant,
+            show_view_handles: true,
+            draw_order: GizmoHandle::default_draw_order,
+            pick_priority: GizmoHandle::default_pick_priority,
             snapping: false,
+            snap_modes: EnumSet::all(),
             snap_angle: DEFAULT_SNAP_ANGLE,
             snap_distance: DEFAULT_SNAP_DISTANCE,
             snap_scale: DEFAULT_SNAP_SCALE,
+            scale_snap_mode: ScaleSnapMode::Additive,
+            uniform_scale_modifier: None,
+            uniform_scale: false,
+            placement_modifier: Modifiers::ALT,
+            placing: false,
+            align_to_normal: false,
+            scale_origin: ScaleOrigin::Center,
+            local_bounds: None,
+            coarse_snap_modifier: None,
+            coarse_snap_angle: DEFAULT_COARSE_SNAP_ANGLE,
+            coarse_snap_distance: DEFAULT_COARSE_SNAP_DISTANCE,
+            arcball_sensitivity: 1.0,
+            arcball_behavior: ArcballBehavior::PointerDelta,
+            touch_gestures: false,
+            set_cursor: true,
+            show_tooltips: false,
+            scroll_nudges: false,
+            proportional_radius: None,
+            force_highlight: None,
+            view_override: None,
+            drag_button: PointerButton::Primary,
+            double_click_resets: false,
+            interactable: true,
+            preview_only: false,
+            smoothing: 0.0,
+            smoothing_factor: 1.0,
+            drag_threshold: DEFAULT_DRAG_THRESHOLD,
             visuals: GizmoVisuals::default(),
             //----------------------------------//
             rotation: DQuat::IDENTITY,
             translation: DVec3::ZERO,
             scale: DVec3::ONE,
+            decomposed: false,
             view_projection: DMat4::IDENTITY,
             mvp: DMat4::IDENTITY,
+            view_axes: DMat3::IDENTITY,
             gizmo_view_forward: DVec3::ONE,
+            visible: false,
+            fade_alpha: 1.0,
             scale_factor: 0.0,
             focus_distance: 0.0,
+            pick_tolerance: None,
+            style_pick_tolerance: 5.0,
             left_handed: false,
+            camera_prepared: false,
         }
     }
 }
@@ -560,34 +3553,113 @@ impl Default for GizmoConfig {
 impl GizmoConfig {
     /// Prepare the gizmo configuration for interaction and rendering.
     /// Some values are precalculated for better performance at the cost of memory usage.
-    fn prepare(&mut self, ui: &Ui) {
+    fn prepare(&mut self, ui: &Ui) -> Result<(), GizmoError> {
         // Use ui clip rect if the user has not specified a viewport
         if self.viewport.is_negative() {
             self.viewport = ui.clip_rect();
         }
 
-        let (scale, rotation, translation) = self.model_matrix.to_scale_rotation_translation();
-        self.rotation = rotation;
-        self.translation = translation;
-        self.scale = scale;
-        self.view_projection = self.projection_matrix * self.view_matrix;
-        self.mvp = self.projection_matrix * self.view_matrix * self.model_matrix;
+        if !(self.viewport.width() > 0.0 && self.viewport.height() > 0.0) {
+            return Err(GizmoError::DegenerateViewport);
+        }
+        if !self.model_matrix.is_finite() {
+            return Err(GizmoError::NonFiniteModelMatrix);
+        }
+        if let Some(parent_matrix) = self.parent_matrix {
+            if !parent_matrix.is_finite() {
+                return Err(GizmoError::NonFiniteModelMatrix);
+            }
+            if parent_matrix.determinant() == 0.0 {
+                return Err(GizmoError::NonInvertibleParentMatrix);
+            }
+            // The gizmo is shown and transformed in world space
+            self.model_matrix = parent_matrix * self.model_matrix;
+            self.decomposed = false;
+        }
+        // Matrices with shear or perspective terms cannot be decomposed. The gizmo is
+        // shown with the closest TRS matrix, so that it still appears at the object,
+        // and the replacement is reported in the debug info.
+        self.non_trs = !self.decomposed && !matrix_is_trs(self.model_matrix, TRS_EPSILON);
+        if self.non_trs {
+            if !self.trs_fallback {
+                return Err(GizmoError::NonTrsModelMatrix);
+            }
+            let (scale, rotation, translation) = orthonormalized_trs(self.model_matrix);
+            self.model_matrix =
+                DMat4::from_scale_rotation_translation(scale, rotation, translation);
+        }
+
+        // Decomposing is not needed if the transformation was given as separate components
+        if !self.decomposed {
+            let (scale, rotation, translation) = self.model_matrix.to_scale_rotation_translation();
+            self.rotation = rotation;
+            self.translation = translation;
+            self.scale = scale;
+        }
+        if self.projection_matrix == DMat4::IDENTITY {
+            return Err(GizmoError::MissingProjection);
+        }
+        // Camera dependent values may have been precalculated by GizmoCameraConfig
+        if !self.camera_prepared {
+            self.view_projection =
+                y_up_projection(self.projection_matrix, self.flip_y) * self.view_matrix;
+            self.left_handed = is_left_handed(self.projection_matrix);
+        }
+
+        if !self.view_projection.is_finite() {
+            return Err(GizmoError::NonFiniteViewProjection);
+        }
+        if self.view_projection.determinant() == 0.0 {
+            return Err(GizmoError::NonInvertibleViewProjection);
+        }
 
-        self.scale_factor = self.mvp.as_ref()[15] as f32
-            / self.projection_matrix.as_ref()[0] as f32
-            / self.viewport.width()
-            * 2.0;
+        self.mvp = self.view_projection * self.model_matrix;
 
-        self.focus_distance = self.scale_factor * (self.visuals.stroke_width / 2.0 + 5.0);
+        // The axes are taken from the camera transformation instead of the rows of the view
+        // matrix, which are only the camera axes if the view matrix has no scaling.
+        let camera_transform = self.view_matrix.inverse();
+        self.view_axes = DMat3::from_cols(
+            camera_transform.x_axis.xyz().normalize_or_zero(),
+            camera_transform.y_axis.xyz().normalize_or_zero(),
+            camera_transform.z_axis.xyz().normalize_or_zero(),
+        );
+        self.fade_alpha = self.distance_fade(camera_transform.w_axis.xyz());
+
+        // The fallback averages the horizontal and vertical scales of the projection,
+        // like the measurement does for non-square pixels. Its offset terms do not
+        // affect the size of a pixel.
+        self.scale_factor = self.world_units_per_pixel().unwrap_or_else(|| {
+            let projection = self.projection_matrix;
+            let pixels_per_unit = (projection.x_axis.x.abs() * self.viewport.width() as f64
+                + projection.y_axis.y.abs() * self.viewport.height() as f64)
+                / 4.0;
+            (self.mvp.w_axis.w / pixels_per_unit) as f32
+        });
+
+        self.style_pick_tolerance = ui.style().interaction.resize_grab_radius_side;
+        self.focus_distance = self.compute_focus_distance();
+
+        // The overridden view axes are set only after the scale factor was measured,
+        // since it depends on the actual camera.
+        let view_override = self.view_override.and_then(|(forward, up)| {
+            // The camera looks along the negative z axis of right handed view spaces
+            let z_axis = if self.left_handed { forward } else { -forward }.try_normalize()?;
+            let x_axis = up.cross(z_axis).try_normalize()?;
+            Some(DMat3::from_cols(x_axis, z_axis.cross(x_axis), z_axis))
+        });
+        if let Some(view_axes) = view_override {
+            self.view_axes = view_axes;
+        }
 
-        self.left_handed = if self.projection_matrix.z_axis.w == 0.0 {
-            self.projection_matrix.z_axis.z > 0.0
-        } else {
-            self.projection_matrix.z_axis.w > 0.0
+        // Nothing can be shown if the gizmo origin does not project to the screen,
+        // e.g. when it is behind the camera.
+        let Some(gizmo_screen_pos) =
+            world_to_screen(self.viewport, self.view_projection, self.translation)
+        else {
+            self.visible = false;
+            return Ok(());
         };
-
-        let gizmo_screen_pos =
-            world_to_screen(self.viewport, self.mvp, self.translation).unwrap_or_default();
+        self.visible = true;
 
         let gizmo_view_near = screen_to_world(
             self.viewport,
@@ -596,28 +3668,168 @@ impl GizmoConfig {
             -1.0,
         );
 
-        self.gizmo_view_forward = (gizmo_view_near - self.translation).normalize_or_zero();
+        self.gizmo_view_forward = if view_override.is_some() {
+            self.view_forward()
+        } else {
+            (gizmo_view_near - self.translation).normalize_or_zero()
+        };
+
+        Ok(())
+    }
+
+    fn debug_info(&self) -> GizmoDebugInfo {
+        GizmoDebugInfo {
+            viewport: self.viewport,
+            left_handed: self.left_handed,
+            orthographic: self.projection_matrix.z_axis.w == 0.0,
+            scale_factor: self.scale_factor,
+            gizmo_screen_pos: world_to_screen(
+                self.viewport,
+                self.view_projection,
+                self.translation,
+            ),
+            culled: !self.visible,
+            non_trs_model_matrix: self.non_trs,
+        }
+    }
+
+    /// Configuration for drawing the shadow of a subgizmo: offset on the screen, in the
+    /// shadow color, and with strokes widened by the blur. See [`GizmoVisuals::shadow`].
+    pub(crate) fn shadow(&self, (offset, color, blur): (Vec2, Color32, f32)) -> Self {
+        let mut config = *self;
+        config.viewport = config.viewport.translate(offset);
+
+        let visuals = &mut config.visuals;
+        visuals.x_color = color;
+        visuals.y_color = color;
+        visuals.z_color = color;
+        visuals.view_ring_color = Some(color);
+        visuals.view_plane_color = Some(color);
+        visuals.plane_colors = None;
+        visuals.highlight_color = None;
+        visuals.color_for = None;
+        visuals.stroke_width += blur;
+
+        config
+    }
+
+    /// Configuration prepared for the given transformation in world space, e.g. the dragged
+    /// pose of a previewed drag. The parent matrix and the TRS check of the model matrix
+    /// given by the host are kept.
+    fn with_world_transform(&self, ui: &Ui, model_matrix: DMat4) -> Result<Self, GizmoError> {
+        let mut config = Self {
+            model_matrix,
+            parent_matrix: None,
+            decomposed: false,
+            ..*self
+        };
+        config.prepare(ui)?;
+        config.parent_matrix = self.parent_matrix;
+        config.non_trs = self.non_trs;
+        Ok(config)
+    }
+
+    /// Configuration with the transformation of `pose` and everything else, such as the
+    /// visuals of a subgizmo, from this one
+    pub(crate) fn with_pose_of(&self, pose: &Self) -> Self {
+        Self {
+            model_matrix: pose.model_matrix,
+            rotation: pose.rotation,
+            translation: pose.translation,
+            scale: pose.scale,
+            mvp: pose.mvp,
+            gizmo_view_forward: pose.gizmo_view_forward,
+            visible: pose.visible,
+            fade_alpha: pose.fade_alpha,
+            scale_factor: pose.scale_factor,
+            focus_distance: pose.focus_distance,
+            ..*self
+        }
+    }
+
+    /// Alpha multiplier of the whole gizmo for the given camera position.
+    /// See [`GizmoVisuals::fade_distance`].
+    fn distance_fade(&self, camera_position: DVec3) -> f32 {
+        let Some((near, far)) = self.visuals.fade_distance else {
+            return 1.0;
+        };
+
+        let distance = camera_position.distance(self.translation) as f32;
+        if distance < near {
+            (distance / near * 2.0 - 1.0).clamp(0.0, 1.0)
+        } else if distance > far {
+            (2.0 - distance / far).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Distance in world units from a subgizmo within which the pointer focuses it
+    fn compute_focus_distance(&self) -> f32 {
+        let pick_tolerance = self.pick_tolerance.unwrap_or(self.style_pick_tolerance);
+        self.scale_factor * (self.visuals.stroke_width / 2.0 + pick_tolerance)
+    }
+
+    /// Size of a screen pixel in world units at the gizmo origin. It is measured by
+    /// projecting view aligned unit vectors to the screen, so that off-center
+    /// projections and projections with different horizontal and vertical
+    /// scales are taken into account.
+    fn world_units_per_pixel(&self) -> Option<f32> {
+        let origin = world_to_screen(self.viewport, self.view_projection, self.translation)?;
+        let right = world_to_screen(
+            self.viewport,
+            self.view_projection,
+            self.translation + self.view_right(),
+        )?;
+        let up = world_to_screen(
+            self.viewport,
+            self.view_projection,
+            self.translation + self.view_up(),
+        )?;
+
+        let pixels_per_unit = (origin.distance(right) + origin.distance(up)) / 2.0;
+        if pixels_per_unit > f32::EPSILON {
+            Some(pixels_per_unit.recip())
+        } else {
+            None
+        }
     }
 
     /// Forward vector of the view camera
     pub(crate) fn view_forward(&self) -> DVec3 {
-        self.view_matrix.row(2).xyz()
+        self.view_axes.z_axis
     }
 
     /// Up vector of the view camera
     pub(crate) fn view_up(&self) -> DVec3 {
-        self.view_matrix.row(1).xyz()
+        self.view_axes.y_axis
     }
 
     /// Right vector of the view camera
     pub(crate) fn view_right(&self) -> DVec3 {
-        self.view_matrix.row(0).xyz()
+        self.view_axes.x_axis
+    }
+
+    /// Translation that keeps the scale origin in place when
+    /// the scale of the object changes from `start_scale` to `scale`
+    pub(crate) fn scale_origin_translation(
+        &self,
+        start_translation: DVec3,
+        start_scale: DVec3,
+        scale: DVec3,
+    ) -> DVec3 {
+        let origin = self.scale_origin.local_point(self.local_bounds);
+        start_translation + self.rotation * ((start_scale - scale) * origin)
+    }
+
+    /// Whether transformations of the given mode are snapped
+    pub(crate) fn snaps(&self, mode: GizmoMode) -> bool {
+        self.snapping && self.snap_modes.contains(mode)
     }
 
     /// Whether local orientation is used
     pub(crate) fn local_space(&self) -> bool {
-        // Scale mode only works in local space
-        self.orientation == GizmoOrientation::Local || self.mode == GizmoMode::Scale
+        self.orientation == GizmoOrientation::Local
     }
 }
 
@@ -628,20 +3840,107 @@ pub(crate) struct Ray {
     direction: DVec3,
 }
 
+impl Ray {
+    /// Ray with its origin relative to the given point
+    pub(crate) fn rebased(self, origin: DVec3) -> Self {
+        Self {
+            origin: self.origin - origin,
+            ..self
+        }
+    }
+}
+
 /// Gizmo state that is saved between frames
 #[derive(Default, Debug, Copy, Clone)]
 struct GizmoState {
     active_subgizmo_id: Option<Id>,
+    /// Mode of the active subgizmo, which is kept even if the modes of the gizmo change
+    active_mode: Option<GizmoMode>,
+    /// Whether the active subgizmo was grabbed with the keyboard
+    keyboard_grab: bool,
+    /// Whether the pointer has moved since the subgizmo was grabbed with the keyboard
+    grab_pointer_moved: bool,
+    /// Whether a touch gesture started over the gizmo is ongoing
+    gesture_active: bool,
+    /// Accumulated zoom of the touch gesture
+    gesture_zoom: f64,
+    /// Accumulated rotation of the touch gesture in radians
+    gesture_rotation: f64,
+    /// Current center of the touches of the gesture
+    gesture_center: Pos2,
+    start_transform: DMat4,
+    drag_start_screen: Pos2,
+    /// Whether the pointer has moved beyond the drag threshold since the press
+    drag_threshold_exceeded: bool,
+    /// Scrolled fraction of a nudge step, not yet applied
+    scroll_remainder: f32,
+    /// Input time when the active drag started
+    drag_start_time: f64,
+    /// Number of frames the active drag has lasted
+    drag_frame_count: u32,
+    /// Proportional editing radius, adjusted during the active drag
+    proportional_radius: f32,
+    /// Input of the last update of the dragged subgizmo
+    update_input: Option<UpdateInput>,
+    /// Incremented whenever a result changes the transformation
+    change_id: u64,
+    /// Dragged pose in world space that is shown instead of the model matrix,
+    /// see [`Gizmo::preview_only`]
+    preview_transform: Option<DMat4>,
+}
+
+/// Input that affects the result of the dragged subgizmo. The result is not returned
+/// on frames where it is the same as on the previous update.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct UpdateInput {
+    pointer: Pos2,
+    modifiers: Modifiers,
+    snapping: bool,
+    proportional_radius: f32,
+}
+
+impl GizmoState {
+    /// Ends the drag of the active subgizmo without a release
+    fn cancel_drag(&mut self) {
+        self.active_subgizmo_id = None;
+        self.active_mode = None;
+        self.keyboard_grab = false;
+        self.drag_threshold_exceeded = false;
+        self.preview_transform = None;
+    }
+}
+
+/// State of the gizmo and its subgizmos. It is loaded from egui memory once
+/// per frame and saved back after the gizmo has been drawn, instead of each
+/// subgizmo accessing egui memory separately.
+#[derive(Default, Clone)]
+struct GizmoMemory {
+    state: GizmoState,
+    subgizmos: IdTypeMap,
 }
 
-pub(crate) trait WidgetData: Sized + Default + Copy + Clone + Send + Sync + 'static {
+impl GizmoMemory {
     fn load(ctx: &Context, gizmo_id: Id) -> Self {
-        ctx.memory_mut(|mem| *mem.data.get_temp_mut_or_default::<Self>(gizmo_id))
+        #[cfg(feature = "bench")]
+        bench::count_memory_access();
+        ctx.memory_mut(|mem| std::mem::take(mem.data.get_temp_mut_or_default::<Self>(gizmo_id)))
     }
 
     fn save(self, ctx: &Context, gizmo_id: Id) {
+        #[cfg(feature = "bench")]
+        bench::count_memory_access();
         ctx.memory_mut(|mem| mem.data.insert_temp(gizmo_id, self));
     }
-}
 
-impl WidgetData for GizmoState {}
+    /// Change id of the gizmo, read without taking the memory, which is not saved back
+    /// on frames where the gizmo is not shown
+    fn change_id(ctx: &Context, gizmo_id: Id) -> u64 {
+        #[cfg(feature = "bench")]
+        bench::count_memory_access();
+        ctx.memory_mut(|mem| {
+            mem.data
+                .get_temp::<Self>(gizmo_id)
+                .map_or(0, |memory| memory.state.change_id)
+        })
+    }
+}
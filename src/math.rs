@@ -1,5 +1,5 @@
 use egui::{Pos2, Rect};
-use glam::{DMat3, DMat4, DVec3, DVec4, Vec4Swizzles};
+use glam::{DMat3, DMat4, DQuat, DVec3, DVec4, Mat4, Vec4Swizzles};
 
 /// Creates a matrix that represents rotation between two 3d vectors
 ///
@@ -155,7 +155,8 @@ pub fn round_to_interval(val: f64, interval: f64) -> f64 {
     (val / interval).round() * interval
 }
 
-/// Calculates 2d screen coordinates from 3d world coordinates
+/// Calculates 2d screen coordinates from 3d world coordinates. The whole matrix is applied
+/// before the perspective division, so off-center and sheared projections are supported.
 pub fn world_to_screen(viewport: Rect, mvp: DMat4, pos: DVec3) -> Option<Pos2> {
     let mut pos = mvp * DVec4::from((pos, 1.0));
 
@@ -174,7 +175,8 @@ pub fn world_to_screen(viewport: Rect, mvp: DMat4, pos: DVec3) -> Option<Pos2> {
     ))
 }
 
-/// Calculates 3d world coordinates from 2d screen coordinates
+/// Calculates 3d world coordinates from 2d screen coordinates. `mat` is the inverse of the
+/// full view projection matrix, so off-center and sheared projections are supported.
 pub fn screen_to_world(viewport: Rect, mat: DMat4, pos: Pos2, z: f64) -> DVec3 {
     let x = ((pos.x - viewport.min.x) / viewport.width()).mul_add(2.0, -1.0) as f64;
     let y = ((pos.y - viewport.min.y) / viewport.height()).mul_add(2.0, -1.0) as f64;
@@ -190,3 +192,115 @@ pub fn screen_to_world(viewport: Rect, mat: DMat4, pos: Pos2, z: f64) -> DVec3 {
 
     world_pos.xyz()
 }
+
+/// Hit tests a polyline given in screen coordinates, such as the projected outline
+/// of a custom handle. Returns the distance in points from the pointer to the closest
+/// segment of the polyline, if it is within the tolerance. Smaller distances are
+/// closer matches.
+pub fn pick_polyline(points: &[Pos2], pointer: Pos2, tolerance: f32) -> Option<f32> {
+    let distance = match points {
+        [] => return None,
+        [point] => point.distance(pointer),
+        _ => points
+            .windows(2)
+            .map(|segment| distance_to_segment(pointer, segment[0], segment[1]))
+            .fold(f32::INFINITY, f32::min),
+    };
+
+    (distance <= tolerance).then_some(distance)
+}
+
+/// Hit tests a polygon given in screen coordinates, such as the projected quad
+/// of a custom handle. Returns zero when the pointer is inside the polygon, and
+/// otherwise the distance in points from the pointer to the closest edge of the
+/// polygon, if it is within the tolerance. See [`pick_polyline`].
+pub fn pick_polygon(points: &[Pos2], pointer: Pos2, tolerance: f32) -> Option<f32> {
+    if polygon_contains(points, pointer) {
+        return Some(0.0);
+    }
+
+    let mut outline = points.to_vec();
+    outline.extend(points.first());
+    pick_polyline(&outline, pointer, tolerance)
+}
+
+/// Whether the matrix is composed of only translation, rotation and scale, and can
+/// be decomposed into them. Matrices with shear or perspective terms are not.
+/// The columns are orthogonal if the dot products of the normalized columns are
+/// within `epsilon` of zero.
+pub fn is_trs(matrix: impl Into<mint::ColumnMatrix4<f32>>, epsilon: f32) -> bool {
+    matrix_is_trs(Mat4::from(matrix.into()).as_dmat4(), epsilon as f64)
+}
+
+pub(crate) fn matrix_is_trs(matrix: DMat4, epsilon: f64) -> bool {
+    let x = matrix.x_axis.xyz().normalize_or_zero();
+    let y = matrix.y_axis.xyz().normalize_or_zero();
+    let z = matrix.z_axis.xyz().normalize_or_zero();
+
+    let projective = DVec4::new(
+        matrix.x_axis.w,
+        matrix.y_axis.w,
+        matrix.z_axis.w,
+        matrix.w_axis.w - 1.0,
+    );
+
+    x.dot(y).abs() <= epsilon
+        && y.dot(z).abs() <= epsilon
+        && z.dot(x).abs() <= epsilon
+        && projective.abs().max_element() <= epsilon
+}
+
+/// Closest translation, rotation and scale to a matrix that is not a pure TRS.
+/// The translation is kept, the rotation is orthonormalized from the columns, and
+/// the scale is the length of the columns.
+pub(crate) fn orthonormalized_trs(matrix: DMat4) -> (DVec3, DQuat, DVec3) {
+    let (x, y, z) = (
+        matrix.x_axis.xyz(),
+        matrix.y_axis.xyz(),
+        matrix.z_axis.xyz(),
+    );
+    let mut scale = DVec3::new(x.length(), y.length(), z.length());
+
+    // Gram-Schmidt, keeping the direction of the x axis
+    let x_axis = x.normalize_or_zero();
+    let y_axis = (y - x_axis * x_axis.dot(y)).normalize_or_zero();
+    let z_axis = x_axis.cross(y_axis);
+    // Mirrored matrices keep their mirroring in the scale
+    if z_axis.dot(z) < 0.0 {
+        scale.z = -scale.z;
+    }
+
+    let rotation = DQuat::from_mat3(&DMat3::from_cols(x_axis, y_axis, z_axis));
+    let rotation = if rotation.is_finite() && z_axis != DVec3::ZERO {
+        rotation.normalize()
+    } else {
+        DQuat::IDENTITY
+    };
+
+    (scale, rotation, matrix.w_axis.xyz())
+}
+
+/// Whether the polygon contains the point, using the even-odd rule
+fn polygon_contains(points: &[Pos2], point: Pos2) -> bool {
+    let mut inside = false;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Distance from a point to the closest point of the line segment from `a` to `b`
+fn distance_to_segment(point: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((point - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(a + ab * t)
+}
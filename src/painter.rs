@@ -1,10 +1,11 @@
 use std::f64::consts::TAU;
 
 use egui::layers::ShapeIdx;
-use egui::{Color32, Pos2, Rect, Shape, Stroke};
+use egui::{Color32, Mesh, Pos2, Rect, Shape, Stroke, Vec2};
 use glam::{DMat4, DVec3};
 
 use crate::math::world_to_screen;
+use crate::StrokeStyle;
 
 const STEPS_PER_RAD: f64 = 20.0;
 
@@ -23,6 +24,11 @@ impl Painter3d {
         }
     }
 
+    /// Matrix used to project the points to the screen
+    pub const fn mvp(&self) -> DMat4 {
+        self.mvp
+    }
+
     fn arc_points(&self, radius: f64, start_angle: f64, end_angle: f64) -> Vec<Pos2> {
         let angle = f64::clamp(end_angle - start_angle, -TAU, TAU);
 
@@ -50,6 +56,7 @@ impl Painter3d {
         start_angle: f64,
         end_angle: f64,
         stroke: impl Into<Stroke>,
+        style: StrokeStyle,
     ) -> ShapeIdx {
         let mut points = self.arc_points(radius, start_angle, end_angle);
 
@@ -61,25 +68,128 @@ impl Painter3d {
 
         if closed {
             points.pop();
-            self.painter.add(Shape::closed_line(points, stroke))
-        } else {
-            self.painter.add(Shape::line(points, stroke))
         }
+
+        self.styled_line(points, closed, stroke.into(), style)
+    }
+
+    pub fn circle(&self, radius: f64, stroke: impl Into<Stroke>, style: StrokeStyle) -> ShapeIdx {
+        self.arc(radius, 0.0, TAU, stroke, style)
     }
 
-    pub fn circle(&self, radius: f64, stroke: impl Into<Stroke>) -> ShapeIdx {
-        self.arc(radius, 0.0, TAU, stroke)
+    /// Filled circle as an anti-aliased mesh
+    pub fn circle_filled(&self, radius: f64, color: Color32) -> ShapeIdx {
+        self.sector_filled(radius, 0.0, TAU, color)
     }
 
-    pub fn filled_circle(&self, radius: f64, color: Color32) -> ShapeIdx {
-        let mut points = self.arc_points(radius, 0.0, TAU);
-        points.pop();
+    /// Filled circular sector between the given angles as an anti-aliased mesh
+    pub fn sector_filled(
+        &self,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        color: Color32,
+    ) -> ShapeIdx {
+        let mut points = self.arc_points(radius, start_angle, end_angle);
+
+        let full_circle = (end_angle - start_angle).abs() >= TAU;
+        if full_circle {
+            points.pop();
+        }
+
+        let Some(center) = self.vec3_to_pos2(DVec3::ZERO) else {
+            return self.painter.add(Shape::Noop);
+        };
+
+        if !full_circle {
+            points.push(center);
+        }
+
+        self.painter.add(self.feathered_fan(center, &points, color))
+    }
+
+    /// Filled polygon as an anti-aliased mesh. The polygon does not need to be convex,
+    /// as long as all of its edges are visible from its centroid.
+    pub fn polygon_filled(&self, points: &[DVec3], color: Color32) -> ShapeIdx {
+        let points = points
+            .iter()
+            .filter_map(|pos| self.vec3_to_pos2(*pos))
+            .collect::<Vec<_>>();
+
+        if points.len() < 3 {
+            return self.painter.add(Shape::Noop);
+        }
+
+        let centroid = points
+            .iter()
+            .fold(Pos2::ZERO, |sum, pos| sum + pos.to_vec2())
+            / points.len() as f32;
 
         self.painter
-            .add(Shape::convex_polygon(points, color, Stroke::NONE))
+            .add(self.feathered_fan(centroid, &points, color))
+    }
+
+    /// Builds a triangle fan from the center to the outline, with the outline
+    /// feathered the same way egui feathers its own shapes.
+    fn feathered_fan(&self, center: Pos2, outline: &[Pos2], color: Color32) -> Mesh {
+        let mut mesh = Mesh::default();
+        let count = outline.len() as u32;
+        if count < 3 {
+            return mesh;
+        }
+
+        let feathering = 1.0 / self.painter.ctx().pixels_per_point();
+
+        // Normals point outwards regardless of the winding of the outline
+        let area = (0..outline.len())
+            .map(|i| {
+                let (a, b) = (outline[i], outline[(i + 1) % outline.len()]);
+                a.x.mul_add(b.y, -b.x * a.y)
+            })
+            .sum::<f32>();
+        let winding = if area < 0.0 { -1.0 } else { 1.0 };
+
+        mesh.colored_vertex(center, color);
+
+        for (i, pos) in outline.iter().enumerate() {
+            let prev = outline[(i + outline.len() - 1) % outline.len()];
+            let next = outline[(i + 1) % outline.len()];
+
+            let n0 = (*pos - prev).normalized().rot90() * winding;
+            let n1 = (next - *pos).normalized().rot90() * winding;
+            let mut normal = (n0 + n1) * 0.5;
+            let length_sq = normal.length_sq();
+            // Keep the feathering width constant at sharp corners
+            if length_sq > 1e-6 {
+                normal /= length_sq;
+            }
+
+            let offset = normal * (feathering * 0.5);
+            mesh.colored_vertex(*pos - offset, color);
+            mesh.colored_vertex(*pos + offset, Color32::TRANSPARENT);
+        }
+
+        for i in 0..count {
+            let inner = 1 + i * 2;
+            let outer = inner + 1;
+            let next_inner = 1 + ((i + 1) % count) * 2;
+            let next_outer = next_inner + 1;
+
+            mesh.add_triangle(0, inner, next_inner);
+            mesh.add_triangle(inner, outer, next_outer);
+            mesh.add_triangle(inner, next_outer, next_inner);
+        }
+
+        mesh
     }
 
-    pub fn line_segment(&self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) {
+    pub fn line_segment(
+        &self,
+        from: DVec3,
+        to: DVec3,
+        stroke: impl Into<Stroke>,
+        style: StrokeStyle,
+    ) {
         let mut points: [Pos2; 2] = Default::default();
 
         for (i, point) in points.iter_mut().enumerate() {
@@ -90,7 +200,7 @@ impl Painter3d {
             }
         }
 
-        self.painter.line_segment(points, stroke);
+        self.styled_line(points.to_vec(), false, stroke.into(), style);
     }
 
     pub fn arrow(&self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) {
@@ -109,26 +219,55 @@ impl Painter3d {
         }
     }
 
-    pub fn polygon(&self, points: &[DVec3], fill: impl Into<Color32>, stroke: impl Into<Stroke>) {
-        let points = points
-            .iter()
-            .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos))
-            .collect::<Vec<_>>();
-
-        if points.len() > 2 {
-            self.painter
-                .add(Shape::convex_polygon(points, fill, stroke));
+    /// Screen aligned filled square with the given side length in points
+    pub fn filled_square(&self, center: DVec3, size: f32, color: Color32) {
+        if let Some(center) = self.vec3_to_pos2(center) {
+            self.painter.rect_filled(
+                Rect::from_center_size(center, Vec2::splat(size)),
+                0.0,
+                color,
+            );
         }
     }
 
-    pub fn polyline(&self, points: &[DVec3], stroke: impl Into<Stroke>) {
+    pub fn polyline(&self, points: &[DVec3], stroke: impl Into<Stroke>, style: StrokeStyle) {
         let points = points
             .iter()
             .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos))
             .collect::<Vec<_>>();
 
         if points.len() > 1 {
-            self.painter.add(Shape::line(points, stroke));
+            self.styled_line(points, false, stroke.into(), style);
+        }
+    }
+
+    /// Adds a line through the given screen space points. Dashes and dots are placed in
+    /// screen space, so that their lengths are consistent regardless of the perspective.
+    fn styled_line(
+        &self,
+        mut points: Vec<Pos2>,
+        closed: bool,
+        stroke: Stroke,
+        style: StrokeStyle,
+    ) -> ShapeIdx {
+        if closed && style != StrokeStyle::Solid {
+            if let Some(first) = points.first().copied() {
+                points.push(first);
+            }
+        }
+
+        match style {
+            StrokeStyle::Solid if closed => self.painter.add(Shape::closed_line(points, stroke)),
+            StrokeStyle::Solid => self.painter.add(Shape::line(points, stroke)),
+            StrokeStyle::Dashed { length, gap } => self
+                .painter
+                .add(Shape::dashed_line(&points, stroke, length, gap)),
+            StrokeStyle::Dotted { spacing } => self.painter.add(Shape::dotted_line(
+                &points,
+                stroke.color,
+                spacing,
+                stroke.width * 0.5,
+            )),
         }
     }
 
@@ -0,0 +1,27 @@
+//! Commonly used types of the crate, to be imported with `use egui_gizmo::prelude::*`.
+//!
+//! ```
+//! use egui_gizmo::prelude::*;
+//!
+//! let viewport = egui_gizmo::egui::Rect::from_min_size(Default::default(), [800.0, 600.0].into());
+//!
+//! let gizmo = Gizmo::new("My gizmo")
+//!     .viewport(viewport)
+//!     .modes(GizmoMode::Translate | GizmoMode::Rotate)
+//!     .orientation(GizmoOrientation::Local)
+//!     .visuals(GizmoVisuals::default());
+//!
+//! let handle = GizmoHandle {
+//!     mode: GizmoMode::Translate,
+//!     direction: GizmoDirection::X,
+//!     transform_kind: Some(TransformKind::Axis),
+//! };
+//! let interaction: GizmoInteraction = GizmoInteraction::default();
+//! let result: Option<GizmoResult> = interaction.result;
+//! # let _ = (gizmo, handle, result);
+//! ```
+
+pub use crate::{
+    Gizmo, GizmoCameraConfig, GizmoDirection, GizmoError, GizmoHandle, GizmoInteraction, GizmoMode,
+    GizmoOrientation, GizmoPlane, GizmoResult, GizmoVisuals, PlanePlacement, TransformKind,
+};
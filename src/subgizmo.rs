@@ -1,9 +1,13 @@
+use std::cell::RefCell;
 use std::hash::Hash;
 use std::ops::Deref;
+use std::rc::Rc;
 
-use egui::{Id, Ui};
+use egui::util::IdTypeMap;
+use egui::{Id, Rect, Ui};
+use glam::DVec3;
 
-use crate::{GizmoConfig, GizmoResult, Ray};
+use crate::{GizmoConfig, GizmoHandle, GizmoMode, GizmoResult, Ray};
 
 pub(crate) use arcball::ArcballSubGizmo;
 pub(crate) use rotation::RotationSubGizmo;
@@ -16,13 +20,9 @@ pub(crate) mod rotation;
 pub(crate) mod scale;
 pub(crate) mod translation;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub(crate) enum TransformKind {
-    Axis,
-    Plane,
-}
-
 pub(crate) trait SubGizmoKind: 'static {
+    /// Mode of the gizmo this kind of subgizmo belongs to
+    const MODE: GizmoMode;
     type Params;
     type State: Copy + Clone + Send + Sync + Default + 'static;
 }
@@ -35,11 +35,15 @@ pub(crate) struct SubGizmoConfig<T: SubGizmoKind> {
     pub(crate) focused: bool,
     /// Whether this subgizmo is active this frame
     pub(crate) active: bool,
-    /// Opacity of the subgizmo for this frame.
-    /// A fully invisible subgizmo cannot be interacted with.
+    /// Opacity of the subgizmo for this frame. It is multiplied with the
+    /// visibility of the subgizmo, which depends on the view direction.
     pub(crate) opacity: f32,
+    /// World space point where the pointer ray intersects this subgizmo
+    pub(crate) interaction_point: Option<DVec3>,
     /// Additional parameters depending on the subgizmo kind
     params: T::Params,
+    /// States of the subgizmos of the gizmo, saved to egui memory by the gizmo
+    states: Rc<RefCell<IdTypeMap>>,
 }
 
 impl<T: SubGizmoKind> Deref for SubGizmoConfig<T> {
@@ -57,10 +61,18 @@ pub(crate) trait SubGizmoBase: 'static {
     fn set_focused(&mut self, focused: bool);
     /// Sets whether this subgizmo is currently active
     fn set_active(&mut self, active: bool);
+    /// Sets the opacity of this subgizmo for this frame
+    fn set_opacity(&mut self, opacity: f32);
+    /// Configuration of the full gizmo, e.g. to draw this subgizmo differently
+    fn config_mut(&mut self) -> &mut GizmoConfig;
     /// Returns true if this subgizmo is currently focused
     fn is_focused(&self) -> bool;
     /// Returns true if this subgizmo is currently active
     fn is_active(&self) -> bool;
+    /// Mode of the gizmo this subgizmo belongs to
+    fn mode(&self) -> GizmoMode;
+    /// World space point where the pointer ray intersects this subgizmo, if known
+    fn interaction_point(&self) -> Option<DVec3>;
 }
 
 impl<T: SubGizmoKind> SubGizmoBase for SubGizmoConfig<T> {
@@ -76,6 +88,14 @@ impl<T: SubGizmoKind> SubGizmoBase for SubGizmoConfig<T> {
         self.active = active;
     }
 
+    fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    fn config_mut(&mut self) -> &mut GizmoConfig {
+        &mut self.config
+    }
+
     fn is_focused(&self) -> bool {
         self.focused
     }
@@ -83,6 +103,14 @@ impl<T: SubGizmoKind> SubGizmoBase for SubGizmoConfig<T> {
     fn is_active(&self) -> bool {
         self.active
     }
+
+    fn mode(&self) -> GizmoMode {
+        T::MODE
+    }
+
+    fn interaction_point(&self) -> Option<DVec3> {
+        self.interaction_point
+    }
 }
 
 pub(crate) trait SubGizmo: SubGizmoBase {
@@ -91,34 +119,62 @@ pub(crate) trait SubGizmo: SubGizmoBase {
     fn pick(&mut self, ui: &Ui, ray: Ray) -> Option<f64>;
     /// Update the subgizmo based on pointer ray and interaction.
     fn update(&mut self, ui: &Ui, ray: Ray) -> Option<GizmoResult>;
+    /// Reset the component of the transformation controlled by this subgizmo,
+    /// leaving the rest of the transformation untouched.
+    fn reset(&self) -> GizmoResult;
+    /// Nudge the component of the transformation controlled by this subgizmo
+    /// by the given number of snapping steps. [`None`] if it can not be nudged.
+    fn nudge(&self, steps: i32) -> Option<GizmoResult>;
+    /// Current value of the component controlled by this subgizmo,
+    /// reported to assistive technology.
+    fn value(&self) -> Option<f64>;
     /// Draw the subgizmo
     fn draw(&mut self, ui: &Ui);
+    /// Screen space bounds of the drawn subgizmo
+    fn screen_bounds(&self) -> Rect;
+    /// Identifies the subgizmo for the user of the gizmo
+    fn handle(&self) -> GizmoHandle;
 }
 
 impl<T> SubGizmoConfig<T>
 where
     T: SubGizmoKind,
 {
-    pub fn new(id_source: impl Hash, config: GizmoConfig, params: T::Params) -> Self {
+    pub fn new(
+        id_source: impl Hash,
+        mut config: GizmoConfig,
+        params: T::Params,
+        states: Rc<RefCell<IdTypeMap>>,
+    ) -> Self {
+        // Each subgizmo only sees whether snapping is enabled for its own mode
+        config.snapping = config.snaps(T::MODE);
+
         Self {
             id: Id::new(id_source),
             config,
             focused: false,
             active: false,
-            opacity: 0.0,
+            opacity: 1.0,
+            interaction_point: None,
             params,
+            states,
         }
     }
 
-    pub fn state(&self, ui: &Ui) -> T::State {
-        ui.ctx()
-            .memory_mut(|mem| *mem.data.get_temp_mut_or_default::<T::State>(self.id))
+    pub fn state(&self) -> T::State {
+        #[cfg(feature = "bench")]
+        crate::bench::count_state_access();
+        *self
+            .states
+            .borrow_mut()
+            .get_temp_mut_or_default::<T::State>(self.id)
     }
 
-    pub fn update_state_with(&self, ui: &Ui, fun: impl FnOnce(&mut T::State)) {
-        let mut state = self.state(ui);
+    pub fn update_state_with(&self, fun: impl FnOnce(&mut T::State)) {
+        let mut state = self.state();
         fun(&mut state);
-        ui.ctx()
-            .memory_mut(|mem| mem.data.insert_temp(self.id, state));
+        #[cfg(feature = "bench")]
+        crate::bench::count_state_access();
+        self.states.borrow_mut().insert_temp(self.id, state);
     }
 }
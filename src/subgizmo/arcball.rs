@@ -1,83 +1,227 @@
-use egui::{Color32, Pos2, Ui};
-use glam::DQuat;
+use egui::{Color32, Pos2, Rect, Ui};
+use glam::{DQuat, DVec3};
 
-use crate::math::screen_to_world;
-use crate::subgizmo::common::{draw_circle, pick_circle};
+use crate::math::{round_to_interval, screen_to_world, world_to_screen};
+use crate::subgizmo::common::{circle_bounds, draw_circle, pick_circle, smooth_rotation};
 use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind};
-use crate::{GizmoConfig, GizmoMode, GizmoResult, Ray, WidgetData};
+use crate::{
+    ArcballBehavior, GizmoConfig, GizmoDirection, GizmoHandle, GizmoMode, GizmoResult, Ray,
+};
 
 pub(crate) type ArcballSubGizmo = SubGizmoConfig<Arcball>;
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct ArcballState {
+    start_pos: Pos2,
     last_pos: Pos2,
+    start_rotation: DQuat,
+    total_rotation: DQuat,
+    /// Smoothed rotation before snapping, see [`Gizmo::smoothing`](crate::Gizmo::smoothing)
+    smoothed_rotation: DQuat,
 }
 
-impl WidgetData for ArcballState {}
-
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct Arcball;
 
 impl SubGizmoKind for Arcball {
+    const MODE: GizmoMode = GizmoMode::Rotate;
     type Params = ();
     type State = ArcballState;
 }
 
 impl SubGizmo for ArcballSubGizmo {
-    fn pick(&mut self, ui: &Ui, ray: Ray) -> Option<f64> {
+    fn pick(&mut self, _ui: &Ui, ray: Ray) -> Option<f64> {
         let pick_result = pick_circle(self, ray, arcball_radius(&self.config), true);
         if !pick_result.picked {
             return None;
         }
 
-        self.update_state_with(ui, |state: &mut ArcballState| {
+        self.update_state_with(|state: &mut ArcballState| {
+            state.start_pos = ray.screen_pos;
             state.last_pos = ray.screen_pos;
+            state.start_rotation = self.config.rotation;
+            state.total_rotation = DQuat::IDENTITY;
+            state.smoothed_rotation = DQuat::IDENTITY;
         });
 
         Some(pick_result.t)
     }
 
-    fn update(&mut self, ui: &Ui, ray: Ray) -> Option<GizmoResult> {
-        let state = self.state(ui);
-
-        let dir = ray.screen_pos - state.last_pos;
-
-        let quat = if dir.length_sq() > f32::EPSILON {
-            let mat = self.config.view_projection.inverse();
-            let a = screen_to_world(self.config.viewport, mat, ray.screen_pos, 0.0);
-            let b = screen_to_world(self.config.viewport, mat, state.last_pos, 0.0);
-            let origin = self.config.view_forward();
-            let a = (a - origin).normalize();
-            let b = (b - origin).normalize();
+    fn update(&mut self, _ui: &Ui, ray: Ray) -> Option<GizmoResult> {
+        let state = self.state();
 
-            DQuat::from_axis_angle(a.cross(b).normalize(), a.dot(b).acos() * 10.0)
-        } else {
-            DQuat::IDENTITY
+        let total_rotation = match self.config.arcball_behavior {
+            ArcballBehavior::PointerDelta => pointer_delta_rotation(self, state, ray),
+            ArcballBehavior::Trackball => trackball_rotation(self, state, ray)?,
         };
-
-        self.update_state_with(ui, |state: &mut ArcballState| {
-            state.last_pos = ray.screen_pos;
+        let total_rotation = smooth_rotation(&self.config, state.smoothed_rotation, total_rotation);
+        self.update_state_with(|state: &mut ArcballState| {
+            state.smoothed_rotation = total_rotation;
         });
 
-        let new_rotation = quat * self.config.rotation;
+        let (axis, raw_angle) = total_rotation.to_axis_angle();
+        let mut angle = raw_angle;
+        if self.config.snapping {
+            angle = round_to_interval(angle, self.config.snap_angle as f64);
+        }
+
+        // The axis comes from the view, so the rotation is applied in world space in both
+        // orientations. The rings do the same with their world space normal, which is
+        // equal to applying the rotation around the local axis in the local orientation.
+        let new_rotation = DQuat::from_axis_angle(axis, angle) * state.start_rotation;
 
         Some(GizmoResult {
-            scale: self.config.scale.as_vec3().into(),
-            rotation: new_rotation.as_quat().into(),
-            translation: self.config.translation.as_vec3().into(),
-            mode: GizmoMode::Rotate,
-            value: None,
+            mode: result_mode(&self.config),
+            direction: Some(GizmoDirection::View),
+            transform_kind: None,
+            value: Some((axis * angle).as_vec3().to_array()),
+            raw_value: Some((axis * raw_angle).as_vec3().to_array()),
+            snapped: self.config.snapping,
+            snap_increment: self.config.snapping.then_some(self.config.snap_angle),
+            ..GizmoResult::from_transform(self.config.scale, new_rotation, self.config.translation)
         })
     }
 
+    fn reset(&self) -> GizmoResult {
+        GizmoResult {
+            mode: result_mode(&self.config),
+            direction: Some(GizmoDirection::View),
+            transform_kind: None,
+            reset: true,
+            ..GizmoResult::from_transform(
+                self.config.scale,
+                DQuat::IDENTITY,
+                self.config.translation,
+            )
+        }
+    }
+
+    fn nudge(&self, _steps: i32) -> Option<GizmoResult> {
+        None
+    }
+
+    fn value(&self) -> Option<f64> {
+        None
+    }
+
     fn draw(&mut self, ui: &Ui) {
-        self.opacity = if self.focused { 0.10 } else { 0.0 };
+        let opacity = self.opacity;
+        self.opacity = if self.focused { opacity * 0.10 } else { 0.0 };
+
+        let color = self
+            .config
+            .visuals
+            .color_for
+            .map_or(Color32::WHITE, |color_for| color_for(self.handle()));
+        draw_circle(self, ui, color, arcball_radius(&self.config), true);
+
+        // Show the bounds of the arcball when it is not surrounded by the rotation subgizmos
+        if !self.config.modes.contains(GizmoMode::Rotate) {
+            self.opacity = opacity;
+            let color = color.linear_multiply(self.config.visuals.inactive_alpha * 0.5);
+            draw_circle(self, ui, color, arcball_radius(&self.config), false);
+        }
+    }
+
+    fn screen_bounds(&self) -> Rect {
+        circle_bounds(&self.config, arcball_radius(&self.config))
+    }
+
+    fn handle(&self) -> GizmoHandle {
+        GizmoHandle {
+            mode: result_mode(&self.config),
+            direction: GizmoDirection::View,
+            transform_kind: None,
+        }
+    }
+}
 
-        draw_circle(self, ui, Color32::WHITE, arcball_radius(&self.config), true);
+/// Mode reported in the results of the arcball. The arcball of a rotation gizmo gives
+/// rotation results, unless the arcball mode is enabled as well.
+fn result_mode(config: &GizmoConfig) -> GizmoMode {
+    if config.modes.contains(GizmoMode::Arcball) {
+        GizmoMode::Arcball
+    } else {
+        GizmoMode::Rotate
     }
 }
 
-/// Radius to use for outer circle subgizmos
+/// Radius to use for the arcball subgizmo
 pub(crate) fn arcball_radius(config: &GizmoConfig) -> f64 {
-    (config.scale_factor * (config.visuals.gizmo_size + config.visuals.stroke_width - 5.0)) as f64
+    (config.scale_factor
+        * config.visuals.gizmo_size.mul_add(
+            config.visuals.arcball_radius,
+            config.visuals.stroke_width - 5.0,
+        )) as f64
+}
+
+/// Rotation of the arcball that accumulates the pointer movement between frames
+fn pointer_delta_rotation(subgizmo: &ArcballSubGizmo, state: ArcballState, ray: Ray) -> DQuat {
+    let dir = ray.screen_pos - state.last_pos;
+
+    let quat = if dir.length_sq() > f32::EPSILON {
+        let mat = subgizmo.config.view_projection.inverse();
+        let a = screen_to_world(subgizmo.config.viewport, mat, ray.screen_pos, 0.0);
+        let b = screen_to_world(subgizmo.config.viewport, mat, state.last_pos, 0.0);
+        // The view forward vector points away from the camera with left handed projections
+        let mut origin = subgizmo.config.view_forward();
+        if subgizmo.config.left_handed {
+            origin *= -1.0;
+        }
+        let a = (a - origin).normalize();
+        let b = (b - origin).normalize();
+
+        // A larger arcball acts as a larger virtual sphere, rotating less per pointer movement
+        let sensitivity = subgizmo.config.arcball_sensitivity as f64
+            / subgizmo.config.visuals.arcball_radius as f64;
+
+        DQuat::from_axis_angle(a.cross(b).normalize(), a.dot(b).acos() * 10.0 * sensitivity)
+    } else {
+        DQuat::IDENTITY
+    };
+
+    let total_rotation = (quat * state.total_rotation).normalize();
+
+    subgizmo.update_state_with(|state: &mut ArcballState| {
+        state.last_pos = ray.screen_pos;
+        state.total_rotation = total_rotation;
+    });
+
+    total_rotation
+}
+
+/// Rotation of the arcball as a virtual trackball, so that the point of the
+/// sphere grabbed at the start of the drag follows the pointer.
+fn trackball_rotation(subgizmo: &ArcballSubGizmo, state: ArcballState, ray: Ray) -> Option<DQuat> {
+    let from = trackball_point(subgizmo, state.start_pos)?;
+    let to = trackball_point(subgizmo, ray.screen_pos)?;
+
+    Some(DQuat::from_rotation_arc(from, to))
+}
+
+/// Projects the screen position onto the trackball sphere around the gizmo. Outside of
+/// the silhouette of the sphere, a hyperbolic sheet is used instead, so that the
+/// rotation changes smoothly when the pointer leaves the sphere.
+fn trackball_point(subgizmo: &ArcballSubGizmo, screen_pos: Pos2) -> Option<DVec3> {
+    let config = &subgizmo.config;
+    let center = world_to_screen(config.viewport, config.view_projection, config.translation)?;
+    let radius = arcball_radius(config) / config.scale_factor as f64;
+    if radius <= 0.0 {
+        return None;
+    }
+
+    let offset = screen_pos - center;
+    let x = offset.x as f64 / radius;
+    let y = -offset.y as f64 / radius;
+
+    let distance_sq = x.mul_add(x, y * y);
+    let z = if distance_sq <= 0.5 {
+        (1.0 - distance_sq).sqrt()
+    } else {
+        0.5 / distance_sq.sqrt()
+    };
+
+    let point = config.view_right() * x + config.view_up() * y + config.gizmo_view_forward * z;
+
+    Some(point.normalize_or_zero()).filter(|point| *point != DVec3::ZERO)
 }
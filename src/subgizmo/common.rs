@@ -1,19 +1,47 @@
-use crate::math::{ray_to_plane_origin, segment_to_segment};
-use egui::{Color32, Stroke, Ui};
-use std::ops::RangeInclusive;
+use crate::math::{pick_polygon, ray_to_plane_origin, segment_to_segment, world_to_screen};
+use egui::{Color32, Rect, Ui, Vec2};
+use std::ops::{Add, Mul, RangeInclusive, Sub};
 
 use crate::painter::Painter3d;
-use crate::subgizmo::{SubGizmoConfig, SubGizmoKind};
-use crate::{GizmoConfig, GizmoDirection, Ray};
+use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind};
+use crate::{GizmoConfig, GizmoDirection, GizmoMode, PlanePlacement, Ray, StrokeStyle};
 use glam::{DMat3, DMat4, DQuat, DVec3};
 
 const ARROW_FADE: RangeInclusive<f64> = 0.95..=0.99;
 const PLANE_FADE: RangeInclusive<f64> = 0.70..=0.86;
 
+/// Length of the negative axis lines relative to the positive ones
+const NEGATIVE_AXIS_LENGTH: f64 = 0.5;
+/// Alpha multiplier of the negative axis lines
+const NEGATIVE_AXIS_ALPHA: f32 = 0.5;
+
+/// Length of the scale arrows relative to the translation arrows,
+/// when both are shown at the same time
+const UNIVERSAL_SCALE_LENGTH: f64 = 0.6;
+
+/// Moves the smoothed drag value of a subgizmo towards the exact one by the smoothing
+/// factor of the frame. See [`Gizmo::smoothing`](crate::Gizmo::smoothing).
+pub(crate) fn smooth<T>(config: &GizmoConfig, smoothed: T, exact: T) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>,
+{
+    if config.smoothing_factor >= 1.0 {
+        return exact;
+    }
+    smoothed + (exact - smoothed) * config.smoothing_factor
+}
+
+/// Same as [`smooth`], for rotations
+pub(crate) fn smooth_rotation(config: &GizmoConfig, smoothed: DQuat, exact: DQuat) -> DQuat {
+    if config.smoothing_factor >= 1.0 {
+        return exact;
+    }
+    smoothed.slerp(exact, config.smoothing_factor)
+}
+
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct PickResult {
     pub subgizmo_point: DVec3,
-    pub visibility: f64,
     pub picked: bool,
     pub t: f64,
 }
@@ -28,70 +56,94 @@ pub(crate) fn pick_arrow<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     ray: Ray,
     direction: GizmoDirection,
+    arrowhead_style: ArrowheadStyle,
 ) -> PickResult {
-    let width = (subgizmo.config.scale_factor * subgizmo.config.visuals.stroke_width) as f64;
-
-    let dir = gizmo_normal(&subgizmo.config, direction);
-    let start = subgizmo.config.translation
-        + (dir * (width.mul_add(0.5, inner_circle_radius(&subgizmo.config))));
-
-    let length = (subgizmo.config.scale_factor * subgizmo.config.visuals.gizmo_size) as f64;
-
-    let ray_length = 1e+14;
+    let config = &subgizmo.config;
+    let dir = gizmo_normal(config, direction);
+
+    let mut start_distance = arrow_start(config);
+    if arrowhead_style == ArrowheadStyle::Cone && config.modes.contains(GizmoMode::Scale) {
+        // Leave room for picking the scale handles that are closer to the origin
+        let scale_handle_end = arrow_end(config, ArrowheadStyle::Square)
+            + arrowhead_length(config, ArrowheadStyle::Square);
+        start_distance = start_distance.max(scale_handle_end + 2.0 * config.focus_distance as f64);
+    }
 
-    let (ray_t, subgizmo_t) = segment_to_segment(
-        ray.origin,
-        ray.origin + ray.direction * ray_length,
-        start,
-        start + dir * length,
-    );
+    let start = config.translation + dir * start_distance;
+    let length = arrow_end(config, arrowhead_style) - start_distance
+        + arrowhead_length(config, arrowhead_style);
 
-    let ray_point = ray.origin + ray.direction * ray_length * ray_t;
-    let subgizmo_point = start + dir * length * subgizmo_t;
-    let dist = (ray_point - subgizmo_point).length();
+    let (mut ray_t, mut subgizmo_point, mut dist) =
+        ray_to_segment(ray, start, start + dir * length);
 
-    let dot = subgizmo.config.gizmo_view_forward.dot(dir).abs();
+    if config.visuals.show_negative_axes {
+        let start = config.translation - dir * arrow_start(config);
+        let end = start - dir * negative_arrow_length(config, arrowhead_style);
+        let negative = ray_to_segment(ray, start, end);
+        if negative.2 < dist {
+            (ray_t, subgizmo_point, dist) = negative;
+        }
+    }
 
-    let visibility =
-        (1.0 - (dot - *ARROW_FADE.start()) / (*ARROW_FADE.end() - *ARROW_FADE.start())).min(1.0);
+    let visibility = arrow_visibility(&subgizmo.config, direction);
 
     let picked = visibility > 0.0 && dist <= subgizmo.config.focus_distance as f64;
 
     PickResult {
         subgizmo_point,
-        visibility,
         picked,
         t: ray_t,
     }
 }
 
+/// Finds the closest points between the ray and the given segment. Returns the
+/// ray parameter, the closest point on the segment and the distance between the points.
+fn ray_to_segment(ray: Ray, start: DVec3, end: DVec3) -> (f64, DVec3, f64) {
+    let ray_length = 1e+14;
+
+    let (ray_t, segment_t) = segment_to_segment(
+        ray.origin,
+        ray.origin + ray.direction * ray_length,
+        start,
+        end,
+    );
+
+    let ray_point = ray.origin + ray.direction * ray_length * ray_t;
+    let segment_point = start.lerp(end, segment_t);
+
+    (ray_t, segment_point, (ray_point - segment_point).length())
+}
+
 pub(crate) fn pick_plane<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     ray: Ray,
     direction: GizmoDirection,
 ) -> PickResult {
-    let origin = plane_global_origin(&subgizmo.config, direction);
+    let config = &subgizmo.config;
+    let origin = plane_global_origin(config, direction);
 
-    let normal = gizmo_normal(&subgizmo.config, direction);
+    let normal = gizmo_normal(config, direction);
 
-    let (t, dist_from_origin) = ray_to_plane_origin(normal, origin, ray.origin, ray.direction);
+    let (t, _) = ray_to_plane_origin(normal, origin, ray.origin, ray.direction);
 
     let ray_point = ray.origin + ray.direction * t;
 
-    let dot = subgizmo
-        .config
-        .gizmo_view_forward
-        .dot(gizmo_normal(&subgizmo.config, direction))
-        .abs();
-    let visibility = (1.0
-        - ((1.0 - dot) - *PLANE_FADE.start()) / (*PLANE_FADE.end() - *PLANE_FADE.start()))
-    .min(1.0);
+    let visibility = plane_visibility(config, direction);
 
-    let picked = visibility > 0.0 && dist_from_origin <= plane_size(&subgizmo.config);
+    // The quad of the plane is hit tested on the screen, with the same tolerance in points
+    // as the other subgizmos
+    let corners = plane_corners(config, direction)
+        .into_iter()
+        .map(|corner| world_to_screen(config.viewport, config.view_projection, corner))
+        .collect::<Option<Vec<_>>>();
+    let tolerance = config.focus_distance / config.scale_factor;
+
+    let picked = visibility > 0.0
+        && corners
+            .is_some_and(|corners| pick_polygon(&corners, ray.screen_pos, tolerance).is_some());
 
     PickResult {
         subgizmo_point: ray_point,
-        visibility,
         picked,
         t,
     }
@@ -120,23 +172,85 @@ pub(crate) fn pick_circle<T: SubGizmoKind>(
 
     PickResult {
         subgizmo_point: hit_pos,
-        visibility: 1.0,
         picked,
         t,
     }
 }
 
+/// Distance from the gizmo origin to the start of arrow subgizmos
+pub(crate) fn arrow_start(config: &GizmoConfig) -> f64 {
+    let width = (config.scale_factor * config.visuals.stroke_width) as f64;
+    let offset =
+        (config.scale_factor * config.visuals.gizmo_size * config.visuals.axis_start_offset) as f64;
+
+    width.mul_add(0.5, offset)
+}
+
+/// Distance from the gizmo origin to the end of the line of arrow subgizmos,
+/// where the arrowhead starts. When translation and scale subgizmos are shown
+/// at the same time, the scale handles are placed closer to the origin.
+pub(crate) fn arrow_end(config: &GizmoConfig, arrowhead_style: ArrowheadStyle) -> f64 {
+    let end = (config.scale_factor * config.visuals.gizmo_size * config.visuals.axis_length) as f64;
+
+    if arrowhead_style == ArrowheadStyle::Square && config.modes.contains(GizmoMode::Translate) {
+        end * UNIVERSAL_SCALE_LENGTH
+    } else {
+        end
+    }
+}
+
+/// Length of the arrowhead of arrow subgizmos
+pub(crate) fn arrowhead_length(config: &GizmoConfig, arrowhead_style: ArrowheadStyle) -> f64 {
+    match arrowhead_style {
+        ArrowheadStyle::Square => (config.scale_factor * config.visuals.scale_handle_size) as f64,
+        ArrowheadStyle::Cone => config.visuals.arrow_head_size.map_or_else(
+            || (config.scale_factor * config.visuals.stroke_width) as f64 * 2.4,
+            |arrow_head_size| {
+                (config.scale_factor * config.visuals.gizmo_size * arrow_head_size) as f64
+            },
+        ),
+    }
+}
+
+/// Length of the line drawn in the negative direction of arrow subgizmos
+pub(crate) fn negative_arrow_length(config: &GizmoConfig, arrowhead_style: ArrowheadStyle) -> f64 {
+    (arrow_end(config, arrowhead_style) - arrow_start(config)) * NEGATIVE_AXIS_LENGTH
+}
+
+/// Visibility of an arrow subgizmo. Arrows fade out when they point towards the camera.
+pub(crate) fn arrow_visibility(config: &GizmoConfig, direction: GizmoDirection) -> f64 {
+    let dot = config
+        .gizmo_view_forward
+        .dot(gizmo_normal(config, direction))
+        .abs();
+
+    (1.0 - (dot - *ARROW_FADE.start()) / (*ARROW_FADE.end() - *ARROW_FADE.start())).min(1.0)
+}
+
+/// Visibility of a plane subgizmo. Planes fade out when they are viewed from the side.
+pub(crate) fn plane_visibility(config: &GizmoConfig, direction: GizmoDirection) -> f64 {
+    let dot = config
+        .gizmo_view_forward
+        .dot(gizmo_normal(config, direction))
+        .abs();
+
+    (1.0 - ((1.0 - dot) - *PLANE_FADE.start()) / (*PLANE_FADE.end() - *PLANE_FADE.start())).min(1.0)
+}
+
 pub(crate) fn draw_arrow<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     ui: &Ui,
     direction: GizmoDirection,
     arrowhead_style: ArrowheadStyle,
-) {
-    if subgizmo.opacity <= 1e-4 {
+) where
+    SubGizmoConfig<T>: SubGizmo,
+{
+    let opacity = subgizmo.opacity * arrow_visibility(&subgizmo.config, direction) as f32;
+    if opacity <= 1e-4 {
         return;
     }
 
-    let color = gizmo_color(subgizmo, direction).gamma_multiply(subgizmo.opacity);
+    let color = gizmo_color(subgizmo, direction).gamma_multiply(opacity);
 
     let transform = if subgizmo.config.local_space() {
         DMat4::from_rotation_translation(subgizmo.config.rotation, subgizmo.config.translation)
@@ -151,27 +265,40 @@ pub(crate) fn draw_arrow<T: SubGizmoKind>(
     );
 
     let direction = gizmo_local_normal(&subgizmo.config, direction);
-    let width = (subgizmo.config.scale_factor * subgizmo.config.visuals.stroke_width) as f64;
-    let length = (subgizmo.config.scale_factor * subgizmo.config.visuals.gizmo_size) as f64;
 
-    let start = direction * width.mul_add(0.5, inner_circle_radius(&subgizmo.config));
-    let end = direction * length;
-    painter.line_segment(start, end, (subgizmo.config.visuals.stroke_width, color));
+    let start = direction * arrow_start(&subgizmo.config);
+    let end = direction * arrow_end(&subgizmo.config, arrowhead_style);
+    painter.line_segment(
+        start,
+        end,
+        (subgizmo.config.visuals.stroke_width, color),
+        StrokeStyle::Solid,
+    );
+
+    if subgizmo.config.visuals.show_negative_axes {
+        let negative_length = negative_arrow_length(&subgizmo.config, arrowhead_style);
+        painter.line_segment(
+            -start,
+            -start - direction * negative_length,
+            (
+                subgizmo.config.visuals.stroke_width,
+                color.gamma_multiply(NEGATIVE_AXIS_ALPHA),
+            ),
+            StrokeStyle::Solid,
+        );
+    }
+
+    let arrow_length = arrowhead_length(&subgizmo.config, arrowhead_style);
 
     match arrowhead_style {
         ArrowheadStyle::Square => {
-            let end_stroke_width = subgizmo.config.visuals.stroke_width * 2.5;
-            let end_length = subgizmo.config.scale_factor * end_stroke_width;
-
-            painter.line_segment(
-                end,
-                end + direction * end_length as f64,
-                (end_stroke_width, color),
+            painter.filled_square(
+                end + direction * arrow_length * 0.5,
+                subgizmo.config.visuals.scale_handle_size,
+                color,
             );
         }
         ArrowheadStyle::Cone => {
-            let arrow_length = width * 2.4;
-
             painter.arrow(
                 end,
                 end + direction * arrow_length,
@@ -185,12 +312,15 @@ pub(crate) fn draw_plane<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     ui: &Ui,
     direction: GizmoDirection,
-) {
-    if subgizmo.opacity <= 1e-4 {
+) where
+    SubGizmoConfig<T>: SubGizmo,
+{
+    let opacity = subgizmo.opacity * plane_visibility(&subgizmo.config, direction) as f32;
+    if opacity <= 1e-4 {
         return;
     }
 
-    let color = gizmo_color(subgizmo, direction).gamma_multiply(subgizmo.opacity);
+    let color = plane_color(subgizmo, direction).gamma_multiply(opacity);
 
     let transform = if subgizmo.config.local_space() {
         DMat4::from_rotation_translation(subgizmo.config.rotation, subgizmo.config.translation)
@@ -209,7 +339,7 @@ pub(crate) fn draw_plane<T: SubGizmoKind>(
     let b = plane_tangent(direction) * scale;
     let origin = plane_local_origin(&subgizmo.config, direction);
 
-    painter.polygon(
+    painter.polygon_filled(
         &[
             origin - b - a,
             origin + b - a,
@@ -217,7 +347,6 @@ pub(crate) fn draw_plane<T: SubGizmoKind>(
             origin - b + a,
         ],
         color,
-        Stroke::NONE,
     );
 }
 
@@ -251,10 +380,102 @@ pub(crate) fn draw_circle<T: SubGizmoKind>(
     );
 
     if filled {
-        painter.filled_circle(radius, color);
+        painter.circle_filled(radius, color);
     } else {
-        painter.circle(radius, (subgizmo.config.visuals.stroke_width, color));
+        painter.circle(
+            radius,
+            (subgizmo.config.visuals.stroke_width, color),
+            StrokeStyle::Solid,
+        );
+    }
+}
+
+/// Screen space bounds of the given world space points, padded by the stroke width
+fn points_bounds(config: &GizmoConfig, points: impl IntoIterator<Item = DVec3>) -> Rect {
+    let mut bounds = Rect::NOTHING;
+    for point in points {
+        if let Some(pos) = world_to_screen(config.viewport, config.view_projection, point) {
+            bounds.extend_with(pos);
+        }
+    }
+
+    if bounds.is_negative() {
+        return Rect::NOTHING;
+    }
+
+    bounds.expand(config.visuals.stroke_width)
+}
+
+/// Screen space bounds of an arrow subgizmo, including the arrowhead
+/// and the negative axis line if it is shown.
+pub(crate) fn arrow_bounds(
+    config: &GizmoConfig,
+    direction: GizmoDirection,
+    arrowhead_style: ArrowheadStyle,
+) -> Rect {
+    let normal = gizmo_normal(config, direction);
+    let start = arrow_start(config);
+    let end = arrow_end(config, arrowhead_style) + arrowhead_length(config, arrowhead_style);
+
+    let mut bounds = points_bounds(
+        config,
+        [
+            config.translation + normal * start,
+            config.translation + normal * end,
+        ],
+    );
+
+    if config.visuals.show_negative_axes {
+        let negative_end = start + negative_arrow_length(config, arrowhead_style);
+        bounds = bounds.union(points_bounds(
+            config,
+            [config.translation - normal * negative_end],
+        ));
+    }
+
+    // Square arrowheads have a fixed size on the screen
+    if arrowhead_style == ArrowheadStyle::Square {
+        bounds = bounds.expand(config.visuals.scale_handle_size * 0.5);
     }
+
+    bounds
+}
+
+/// Screen space bounds of a plane subgizmo
+pub(crate) fn plane_bounds(config: &GizmoConfig, direction: GizmoDirection) -> Rect {
+    points_bounds(config, plane_corners(config, direction))
+}
+
+/// Corners of the quad of a plane subgizmo in world space
+fn plane_corners(config: &GizmoConfig, direction: GizmoDirection) -> [DVec3; 4] {
+    let scale = plane_size(config) * 0.5;
+    let mut a = plane_bitangent(direction) * scale;
+    let mut b = plane_tangent(direction) * scale;
+    if config.local_space() {
+        a = config.rotation * a;
+        b = config.rotation * b;
+    }
+    let origin = plane_global_origin(config, direction);
+
+    [
+        origin - b - a,
+        origin + b - a,
+        origin + b + a,
+        origin - b + a,
+    ]
+}
+
+/// Screen space bounds of a circle around the gizmo origin. The bounds are
+/// conservative for circles that are not aligned with the view.
+pub(crate) fn circle_bounds(config: &GizmoConfig, radius: f64) -> Rect {
+    let Some(center) = world_to_screen(config.viewport, config.view_projection, config.translation)
+    else {
+        return Rect::NOTHING;
+    };
+
+    let radius = radius as f32 / config.scale_factor;
+
+    Rect::from_center_size(center, Vec2::splat(radius * 2.0)).expand(config.visuals.stroke_width)
 }
 
 pub(crate) const fn plane_bitangent(direction: GizmoDirection) -> DVec3 {
@@ -276,6 +497,10 @@ pub(crate) const fn plane_tangent(direction: GizmoDirection) -> DVec3 {
 }
 
 pub(crate) fn plane_size(config: &GizmoConfig) -> f64 {
+    if let Some((_, plane_size)) = config.planar {
+        return (config.scale_factor * config.visuals.gizmo_size * plane_size) as f64;
+    }
+
     (config.scale_factor
         * config
             .visuals
@@ -284,6 +509,10 @@ pub(crate) fn plane_size(config: &GizmoConfig) -> f64 {
 }
 
 pub(crate) fn plane_local_origin(config: &GizmoConfig, direction: GizmoDirection) -> DVec3 {
+    if config.plane_placement == PlanePlacement::Centered {
+        return DVec3::ZERO;
+    }
+
     let offset = config.scale_factor * config.visuals.gizmo_size * 0.5;
 
     let a = plane_bitangent(direction);
@@ -310,12 +539,7 @@ pub(crate) fn outer_circle_radius(config: &GizmoConfig) -> f64 {
 }
 
 pub(crate) fn gizmo_local_normal(config: &GizmoConfig, direction: GizmoDirection) -> DVec3 {
-    match direction {
-        GizmoDirection::X => DVec3::X,
-        GizmoDirection::Y => DVec3::Y,
-        GizmoDirection::Z => DVec3::Z,
-        GizmoDirection::View => -config.view_forward(),
-    }
+    direction.axis().unwrap_or_else(|| -config.view_forward())
 }
 
 pub(crate) fn gizmo_normal(config: &GizmoConfig, direction: GizmoDirection) -> DVec3 {
@@ -331,14 +555,82 @@ pub(crate) fn gizmo_normal(config: &GizmoConfig, direction: GizmoDirection) -> D
 pub(crate) fn gizmo_color<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     direction: GizmoDirection,
-) -> Color32 {
-    let color = match direction {
+) -> Color32
+where
+    SubGizmoConfig<T>: SubGizmo,
+{
+    highlighted_color(
+        subgizmo,
+        axis_color(subgizmo, direction),
+        subgizmo.config.visuals.highlight_alpha,
+    )
+}
+
+/// Color of the axis with the given direction, before highlighting
+fn axis_color<T: SubGizmoKind>(subgizmo: &SubGizmoConfig<T>, direction: GizmoDirection) -> Color32
+where
+    SubGizmoConfig<T>: SubGizmo,
+{
+    if let Some(color_for) = subgizmo.config.visuals.color_for {
+        return color_for(subgizmo.handle());
+    }
+
+    match direction {
         GizmoDirection::X => subgizmo.config.visuals.x_color,
         GizmoDirection::Y => subgizmo.config.visuals.y_color,
         GizmoDirection::Z => subgizmo.config.visuals.z_color,
-        GizmoDirection::View => subgizmo.config.visuals.s_color,
+        GizmoDirection::View => view_color(subgizmo),
+    }
+}
+
+/// Color of a view aligned subgizmo. The rotation ring and the
+/// translation and scale handles can have different colors.
+fn view_color<T: SubGizmoKind>(subgizmo: &SubGizmoConfig<T>) -> Color32 {
+    let visuals = &subgizmo.config.visuals;
+    let color = if T::MODE == GizmoMode::Rotate {
+        visuals.view_ring_color
+    } else {
+        visuals.view_plane_color
+    };
+
+    #[allow(deprecated)]
+    color.unwrap_or(visuals.s_color)
+}
+
+/// Color of a plane subgizmo. The color of the axis perpendicular to the plane
+/// is used, unless the plane colors are given in the visuals. The highlighted
+/// plane is filled with its own alpha, if one is given in the visuals.
+fn plane_color<T: SubGizmoKind>(subgizmo: &SubGizmoConfig<T>, direction: GizmoDirection) -> Color32
+where
+    SubGizmoConfig<T>: SubGizmo,
+{
+    let visuals = &subgizmo.config.visuals;
+    let index = match direction {
+        GizmoDirection::Z => 0,
+        GizmoDirection::Y => 1,
+        GizmoDirection::X => 2,
+        GizmoDirection::View => return gizmo_color(subgizmo, direction),
     };
 
+    let color = match (visuals.color_for, visuals.plane_colors) {
+        (Some(color_for), _) => color_for(subgizmo.handle()),
+        (None, Some(colors)) => colors[index],
+        (None, None) => axis_color(subgizmo, direction),
+    };
+    let highlight_alpha = visuals
+        .plane_highlight_fill_alpha
+        .unwrap_or(visuals.highlight_alpha);
+
+    highlighted_color(subgizmo, color, highlight_alpha)
+}
+
+/// Applies the highlight color and the given highlight alpha, or the inactive alpha
+/// of the visuals, to the color of a subgizmo
+fn highlighted_color<T: SubGizmoKind>(
+    subgizmo: &SubGizmoConfig<T>,
+    color: Color32,
+    highlight_alpha: f32,
+) -> Color32 {
     let color = if subgizmo.focused {
         subgizmo.config.visuals.highlight_color.unwrap_or(color)
     } else {
@@ -346,7 +638,7 @@ pub(crate) fn gizmo_color<T: SubGizmoKind>(
     };
 
     let alpha = if subgizmo.focused {
-        subgizmo.config.visuals.highlight_alpha
+        highlight_alpha
     } else {
         subgizmo.config.visuals.inactive_alpha
     };
@@ -1,13 +1,15 @@
 use std::f64::consts::{FRAC_PI_2, PI, TAU};
 
-use egui::Ui;
-use glam::{DMat3, DMat4, DQuat, DVec2, DVec3};
+use egui::{Rect, Ui};
+use glam::{DMat3, DMat4, DQuat, DVec2, DVec3, EulerRot};
 
 use crate::math::{ray_to_plane_origin, rotation_align, round_to_interval, world_to_screen};
 use crate::painter::Painter3d;
-use crate::subgizmo::common::{gizmo_color, gizmo_local_normal, gizmo_normal, outer_circle_radius};
+use crate::subgizmo::common::{
+    circle_bounds, gizmo_color, gizmo_local_normal, gizmo_normal, outer_circle_radius, smooth,
+};
 use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind};
-use crate::{GizmoDirection, GizmoMode, GizmoResult, Ray};
+use crate::{GizmoDirection, GizmoHandle, GizmoMode, GizmoResult, Ray, StrokeStyle, TransformKind};
 
 pub(crate) type RotationSubGizmo = SubGizmoConfig<Rotation>;
 
@@ -18,16 +20,23 @@ pub(crate) struct RotationParams {
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct RotationState {
+    start_rotation: DQuat,
+    /// Angle of the grabbed point of the ring, in the plane of the drawn arc
     start_axis_angle: f32,
     start_rotation_angle: f32,
     last_rotation_angle: f32,
-    current_delta: f32,
+    current_delta: f64,
+    last_raw_rotation_angle: f32,
+    raw_delta: f64,
+    /// Smoothed rotation angle before snapping, see [`Gizmo::smoothing`](crate::Gizmo::smoothing)
+    smoothed_delta: f64,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct Rotation;
 
 impl SubGizmoKind for Rotation {
+    const MODE: GizmoMode = GizmoMode::Rotate;
     type Params = RotationParams;
     type State = RotationState;
 }
@@ -50,6 +59,8 @@ impl SubGizmo for RotationSubGizmo {
 
         let offset = (nearest_circle_pos - origin).normalize();
 
+        self.interaction_point = Some(nearest_circle_pos);
+
         let angle = if self.direction == GizmoDirection::View {
             f64::atan2(tangent.cross(normal).dot(offset), tangent.dot(offset))
         } else {
@@ -60,12 +71,16 @@ impl SubGizmo for RotationSubGizmo {
             f64::atan2(offset.cross(forward).dot(normal), offset.dot(forward))
         };
 
-        self.update_state_with(ui, |state: &mut RotationState| {
+        self.update_state_with(|state: &mut RotationState| {
             let rotation_angle = rotation_angle(self, ui).unwrap_or(0.0);
-            state.start_axis_angle = angle as f32;
+            state.start_rotation = self.config.rotation;
+            state.start_axis_angle = arc_plane_angle(self, offset) as f32;
             state.start_rotation_angle = rotation_angle as f32;
             state.last_rotation_angle = rotation_angle as f32;
             state.current_delta = 0.0;
+            state.last_raw_rotation_angle = rotation_angle as f32;
+            state.raw_delta = 0.0;
+            state.smoothed_delta = 0.0;
         });
 
         if dist_from_gizmo_edge <= config.focus_distance as f64 && angle.abs() < arc_angle(self) {
@@ -76,10 +91,16 @@ impl SubGizmo for RotationSubGizmo {
     }
 
     fn update(&mut self, ui: &Ui, _ray: Ray) -> Option<GizmoResult> {
-        let state = self.state(ui);
+        let state = self.state();
         let config = self.config;
 
-        let mut rotation_angle = rotation_angle(self, ui)?;
+        let raw_rotation_angle = rotation_angle(self, ui)?;
+        let raw_angle_delta =
+            smallest_angle(raw_rotation_angle - state.last_raw_rotation_angle as f64);
+        let raw_delta = state.raw_delta + raw_angle_delta;
+        let smoothed_delta = smooth(&config, state.smoothed_delta, raw_delta);
+
+        let mut rotation_angle = state.start_rotation_angle as f64 + smoothed_delta;
         if config.snapping {
             rotation_angle = round_to_interval(
                 rotation_angle - state.start_rotation_angle as f64,
@@ -87,38 +108,86 @@ impl SubGizmo for RotationSubGizmo {
             ) + state.start_rotation_angle as f64;
         }
 
-        let mut angle_delta = rotation_angle - state.last_rotation_angle as f64;
-
-        // Always take the smallest angle, e.g. -10° instead of 350°
-        if angle_delta > PI {
-            angle_delta -= TAU;
-        } else if angle_delta < -PI {
-            angle_delta += TAU;
-        }
+        let angle_delta = smallest_angle(rotation_angle - state.last_rotation_angle as f64);
 
-        self.update_state_with(ui, |state: &mut RotationState| {
+        self.update_state_with(|state: &mut RotationState| {
             state.last_rotation_angle = rotation_angle as f32;
-            state.current_delta += angle_delta as f32;
+            state.current_delta += angle_delta;
+            state.last_raw_rotation_angle = raw_rotation_angle as f32;
+            state.raw_delta = raw_delta;
+            state.smoothed_delta = smoothed_delta;
         });
 
-        let new_rotation =
-            DQuat::from_axis_angle(gizmo_normal(&self.config, self.direction), -angle_delta)
-                * self.config.rotation;
+        let current_delta = state.current_delta + angle_delta;
+
+        // The drawn arc sweeps around the negated normal, so the rotation is
+        // around the normal by the negated angle. This matches the arc for both
+        // left and right handed projections, as the arc is drawn in world space.
+        let normal = gizmo_normal(&self.config, self.direction);
+        let angle = -current_delta;
+
+        // The rotation is applied to the rotation at the start of the drag,
+        // so that the error does not accumulate over the frames.
+        let new_rotation = DQuat::from_axis_angle(normal, angle) * state.start_rotation;
+
+        Some(GizmoResult {
+            mode: GizmoMode::Rotate,
+            direction: Some(self.direction),
+            transform_kind: Some(TransformKind::Axis),
+            value: Some((normal * angle).as_vec3().to_array()),
+            raw_value: Some((normal * -smoothed_delta).as_vec3().to_array()),
+            snapped: config.snapping,
+            snap_increment: config.snapping.then_some(config.snap_angle),
+            ..GizmoResult::from_transform(self.config.scale, new_rotation, self.config.translation)
+        })
+    }
+
+    fn reset(&self) -> GizmoResult {
+        let (x, y, z) = self.config.rotation.to_euler(EulerRot::XYZ);
+
+        let new_rotation = match self.direction {
+            GizmoDirection::X => DQuat::from_euler(EulerRot::XYZ, 0.0, y, z),
+            GizmoDirection::Y => DQuat::from_euler(EulerRot::XYZ, x, 0.0, z),
+            GizmoDirection::Z => DQuat::from_euler(EulerRot::XYZ, x, y, 0.0),
+            GizmoDirection::View => DQuat::IDENTITY,
+        };
+
+        GizmoResult {
+            mode: GizmoMode::Rotate,
+            direction: Some(self.direction),
+            transform_kind: Some(TransformKind::Axis),
+            reset: true,
+            ..GizmoResult::from_transform(self.config.scale, new_rotation, self.config.translation)
+        }
+    }
+
+    fn nudge(&self, steps: i32) -> Option<GizmoResult> {
+        let normal = gizmo_normal(&self.config, self.direction);
+        let angle = steps as f64 * self.config.snap_angle as f64;
+        let new_rotation = DQuat::from_axis_angle(normal, angle) * self.config.rotation;
 
         Some(GizmoResult {
-            scale: self.config.scale.as_vec3().into(),
-            rotation: new_rotation.as_quat().into(),
-            translation: self.config.translation.as_vec3().into(),
             mode: GizmoMode::Rotate,
-            value: Some(
-                (gizmo_normal(&self.config, self.direction).as_vec3() * state.current_delta)
-                    .to_array(),
-            ),
+            direction: Some(self.direction),
+            transform_kind: Some(TransformKind::Axis),
+            value: Some((normal * angle).as_vec3().to_array()),
+            ..GizmoResult::from_transform(self.config.scale, new_rotation, self.config.translation)
         })
     }
 
+    fn value(&self) -> Option<f64> {
+        let (x, y, z) = self.config.rotation.to_euler(EulerRot::XYZ);
+
+        match self.direction {
+            GizmoDirection::X => Some(x),
+            GizmoDirection::Y => Some(y),
+            GizmoDirection::Z => Some(z),
+            GizmoDirection::View => None,
+        }
+    }
+
     fn draw(&mut self, ui: &Ui) {
-        let state = self.state(ui);
+        let state = self.state();
         let config = self.config;
 
         let transform = rotation_matrix(self);
@@ -128,17 +197,23 @@ impl SubGizmo for RotationSubGizmo {
             config.viewport,
         );
 
-        let color = gizmo_color(self, self.direction);
+        let color = gizmo_color(self, self.direction).gamma_multiply(self.opacity);
         let stroke = (config.visuals.stroke_width, color);
 
         let radius = arc_radius(self);
 
         if !self.active {
             let angle = arc_angle(self);
-            painter.arc(radius, FRAC_PI_2 - angle, FRAC_PI_2 + angle, stroke);
+            painter.arc(
+                radius,
+                FRAC_PI_2 - angle,
+                FRAC_PI_2 + angle,
+                stroke,
+                StrokeStyle::Solid,
+            );
         } else {
-            let start_angle = state.start_axis_angle as f64 + FRAC_PI_2;
-            let end_angle = start_angle + state.current_delta as f64;
+            let start_angle = state.start_axis_angle as f64;
+            let end_angle = start_angle + state.current_delta;
 
             // The polyline does not get rendered correctly if
             // the start and end lines are exactly the same
@@ -151,9 +226,10 @@ impl SubGizmo for RotationSubGizmo {
                     DVec3::new(end_angle.cos() * radius, 0.0, end_angle.sin() * radius),
                 ],
                 stroke,
+                StrokeStyle::Solid,
             );
 
-            painter.circle(radius, stroke);
+            painter.circle(radius, stroke, StrokeStyle::Solid);
 
             // Draw snapping ticks
             if config.snapping {
@@ -165,11 +241,24 @@ impl SubGizmo for RotationSubGizmo {
                         pos * radius * 1.1,
                         pos * radius * 1.2,
                         (stroke_width, stroke.1),
+                        StrokeStyle::Solid,
                     );
                 }
             }
         }
     }
+
+    fn screen_bounds(&self) -> Rect {
+        circle_bounds(&self.config, arc_radius(self))
+    }
+
+    fn handle(&self) -> GizmoHandle {
+        GizmoHandle {
+            mode: GizmoMode::Rotate,
+            direction: self.direction,
+            transform_kind: Some(TransformKind::Axis),
+        }
+    }
 }
 
 /// Calculates angle of the rotation axis arc.
@@ -190,6 +279,15 @@ fn arc_angle(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
     angle
 }
 
+/// Angle of the given direction in the plane of the arc drawn with [`rotation_matrix`].
+/// Unlike the angle from the view direction, it is also defined for rings facing the camera.
+fn arc_plane_angle(subgizmo: &SubGizmoConfig<Rotation>, direction: DVec3) -> f64 {
+    let local = rotation_matrix(subgizmo)
+        .inverse()
+        .transform_vector3(direction);
+    f64::atan2(local.z, local.x)
+}
+
 /// Calculates a matrix used when rendering the rotation axis.
 fn rotation_matrix(subgizmo: &SubGizmoConfig<Rotation>) -> DMat4 {
     if subgizmo.direction == GizmoDirection::View {
@@ -240,6 +338,10 @@ fn rotation_angle(subgizmo: &SubGizmoConfig<Rotation>, ui: &Ui) -> Option<f64> {
         return None;
     }
 
+    // Screen space angle grows clockwise. Positive rotation around an axis that points
+    // towards the viewer appears counterclockwise with a right handed projection and
+    // clockwise with a left handed one. The view forward vector points towards the
+    // viewer only with a right handed projection, so it is not flipped here.
     let mut angle = f64::atan2(delta.y, delta.x);
     if subgizmo
         .config
@@ -253,6 +355,17 @@ fn rotation_angle(subgizmo: &SubGizmoConfig<Rotation>, ui: &Ui) -> Option<f64> {
     Some(angle)
 }
 
+/// Wraps an angle difference to the smallest equivalent angle, e.g. -10° instead of 350°
+fn smallest_angle(angle: f64) -> f64 {
+    if angle > PI {
+        angle - TAU
+    } else if angle < -PI {
+        angle + TAU
+    } else {
+        angle
+    }
+}
+
 fn tangent(subgizmo: &SubGizmoConfig<Rotation>) -> DVec3 {
     let mut tangent = match subgizmo.direction {
         GizmoDirection::X | GizmoDirection::Y => DVec3::Z,
@@ -268,9 +381,17 @@ fn tangent(subgizmo: &SubGizmoConfig<Rotation>) -> DVec3 {
 }
 
 fn arc_radius(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
-    if subgizmo.direction == GizmoDirection::View {
+    let radius = if subgizmo.direction == GizmoDirection::View {
         outer_circle_radius(&subgizmo.config)
     } else {
         (subgizmo.config.scale_factor * subgizmo.config.visuals.gizmo_size) as f64
+    };
+
+    // Make room for the arrows of the other subgizmos
+    let modes = subgizmo.config.modes;
+    if modes.contains(GizmoMode::Translate) || modes.contains(GizmoMode::Scale) {
+        radius * 1.2
+    } else {
+        radius
     }
 }
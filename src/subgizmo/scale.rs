@@ -1,15 +1,15 @@
-use egui::Ui;
+use egui::{Pos2, Rect, Ui};
 use glam::DVec3;
 
-use crate::math::{round_to_interval, world_to_screen};
+use crate::math::world_to_screen;
 
 use crate::subgizmo::common::{
-    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_local_normal, inner_circle_radius,
-    outer_circle_radius, pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_tangent,
-    ArrowheadStyle,
+    arrow_bounds, circle_bounds, draw_arrow, draw_circle, draw_plane, gizmo_color,
+    gizmo_local_normal, inner_circle_radius, outer_circle_radius, pick_arrow, pick_circle,
+    pick_plane, plane_bitangent, plane_bounds, plane_tangent, smooth, ArrowheadStyle, PickResult,
 };
-use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind, TransformKind};
-use crate::{GizmoDirection, GizmoMode, GizmoResult, Ray};
+use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind};
+use crate::{GizmoDirection, GizmoHandle, GizmoMode, GizmoResult, Ray, TransformKind};
 
 pub(crate) type ScaleSubGizmo = SubGizmoConfig<Scale>;
 
@@ -22,38 +22,40 @@ pub(crate) struct ScaleParams {
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct ScaleState {
     start_scale: DVec3,
+    start_translation: DVec3,
+    /// Screen position of the gizmo origin when the drag started. The scale is measured
+    /// from it, as the origin moves during the drag when a side of the object is kept
+    /// in place, see [`ScaleOrigin`](crate::ScaleOrigin).
+    start_origin: Pos2,
     start_delta: f64,
+    /// Smoothed scale factor before snapping, see [`Gizmo::smoothing`](crate::Gizmo::smoothing)
+    smoothed_delta: f64,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct Scale;
 
 impl SubGizmoKind for Scale {
+    const MODE: GizmoMode = GizmoMode::Scale;
     type Params = ScaleParams;
     type State = ScaleState;
 }
 
 impl SubGizmo for ScaleSubGizmo {
     fn pick(&mut self, ui: &Ui, ray: Ray) -> Option<f64> {
-        let pick_result = match (self.transform_kind, self.direction) {
-            (TransformKind::Plane, GizmoDirection::View) => {
-                let mut result = pick_circle(self, ray, inner_circle_radius(&self.config), true);
-                if !result.picked {
-                    result = pick_circle(self, ray, outer_circle_radius(&self.config), false);
-                }
-                result
-            }
-            (TransformKind::Plane, _) => pick_plane(self, ray, self.direction),
-            (TransformKind::Axis, _) => pick_arrow(self, ray, self.direction),
-        };
+        let pick_result = pick_scale(self, ray);
 
-        let start_delta = distance_from_origin_2d(self, ui)?;
+        let start_origin = origin_screen_pos(self)?;
+        let start_delta = distance_from_origin_2d(ui, start_origin)?;
 
-        self.opacity = pick_result.visibility as _;
+        self.interaction_point = Some(pick_result.subgizmo_point);
 
-        self.update_state_with(ui, |state: &mut ScaleState| {
+        self.update_state_with(|state: &mut ScaleState| {
             state.start_scale = self.config.scale;
+            state.start_translation = self.config.translation;
+            state.start_origin = start_origin;
             state.start_delta = start_delta;
+            state.smoothed_delta = 1.0;
         });
 
         if pick_result.picked {
@@ -63,17 +65,31 @@ impl SubGizmo for ScaleSubGizmo {
         }
     }
 
-    fn update(&mut self, ui: &Ui, _ray: Ray) -> Option<GizmoResult> {
-        let state = self.state(ui);
-        let mut delta = distance_from_origin_2d(self, ui)?;
-        delta /= state.start_delta;
+    fn update(&mut self, ui: &Ui, ray: Ray) -> Option<GizmoResult> {
+        let state = self.state();
+        self.interaction_point = Some(pick_scale(self, ray).subgizmo_point);
 
+        let raw_delta = smooth(
+            &self.config,
+            state.smoothed_delta,
+            distance_from_origin_2d(ui, state.start_origin)? / state.start_delta,
+        );
+        self.update_state_with(|state: &mut ScaleState| {
+            state.smoothed_delta = raw_delta;
+        });
+
+        let mut delta = raw_delta;
         if self.config.snapping {
-            delta = round_to_interval(delta, self.config.snap_scale as f64);
+            delta = self
+                .config
+                .scale_snap_mode
+                .snap(delta, self.config.snap_scale);
         }
-        delta = delta.max(1e-4) - 1.0;
+        let delta = delta.max(1e-4) - 1.0;
+        let raw_delta = raw_delta.max(1e-4) - 1.0;
 
         let direction = match (self.transform_kind, self.direction) {
+            _ if self.config.uniform_scale => DVec3::ONE,
             (TransformKind::Axis, _) => gizmo_local_normal(&self.config, self.direction),
             (TransformKind::Plane, GizmoDirection::View) => DVec3::ONE,
             (TransformKind::Plane, _) => {
@@ -82,14 +98,91 @@ impl SubGizmo for ScaleSubGizmo {
         };
 
         let offset = DVec3::ONE + (direction * delta);
+        let raw_offset = DVec3::ONE + (direction * raw_delta);
         let new_scale = state.start_scale * offset;
+        let new_translation = self.config.scale_origin_translation(
+            state.start_translation,
+            state.start_scale,
+            new_scale,
+        );
 
         Some(GizmoResult {
-            scale: new_scale.as_vec3().into(),
-            rotation: self.config.rotation.as_quat().into(),
-            translation: self.config.translation.as_vec3().into(),
             mode: GizmoMode::Scale,
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
             value: Some(offset.as_vec3().to_array()),
+            raw_value: Some(raw_offset.as_vec3().to_array()),
+            snapped: self.config.snapping,
+            snap_increment: if self.config.snapping {
+                self.config
+                    .scale_snap_mode
+                    .increment(self.config.snap_scale)
+            } else {
+                None
+            },
+            ..GizmoResult::from_transform(new_scale, self.config.rotation, new_translation)
+        })
+    }
+
+    fn reset(&self) -> GizmoResult {
+        let normal = gizmo_local_normal(&self.config, self.direction);
+        let scale = self.config.scale;
+
+        let new_scale = match (self.transform_kind, self.direction) {
+            (TransformKind::Axis, _) => scale * (DVec3::ONE - normal) + normal,
+            (TransformKind::Plane, GizmoDirection::View) => DVec3::ONE,
+            (TransformKind::Plane, _) => scale * normal + (DVec3::ONE - normal),
+        };
+        let new_translation =
+            self.config
+                .scale_origin_translation(self.config.translation, scale, new_scale);
+
+        GizmoResult {
+            mode: GizmoMode::Scale,
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
+            reset: true,
+            ..GizmoResult::from_transform(new_scale, self.config.rotation, new_translation)
+        }
+    }
+
+    fn nudge(&self, steps: i32) -> Option<GizmoResult> {
+        let normal = gizmo_local_normal(&self.config, self.direction);
+
+        let direction = match (self.transform_kind, self.direction) {
+            (TransformKind::Axis, _) => normal,
+            (TransformKind::Plane, GizmoDirection::View) => DVec3::ONE,
+            (TransformKind::Plane, _) => DVec3::ONE - normal,
+        };
+
+        // Geometric snap steps multiply the scale, e.g. doubling it with powers of two
+        let new_scale = match self.config.scale_snap_mode.step_factor() {
+            Some(factor) => {
+                self.config.scale * (DVec3::ONE + direction * (factor.powi(steps) - 1.0))
+            }
+            None => self.config.scale + direction * steps as f64 * self.config.snap_scale as f64,
+        }
+        .max(DVec3::splat(1e-4));
+        let new_translation = self.config.scale_origin_translation(
+            self.config.translation,
+            self.config.scale,
+            new_scale,
+        );
+
+        Some(GizmoResult {
+            mode: GizmoMode::Scale,
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
+            value: Some((new_scale / self.config.scale).as_vec3().to_array()),
+            ..GizmoResult::from_transform(new_scale, self.config.rotation, new_translation)
+        })
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.transform_kind == TransformKind::Axis).then(|| {
+            self.config
+                .scale
+                .dot(gizmo_local_normal(&self.config, self.direction))
         })
     }
 
@@ -117,12 +210,52 @@ impl SubGizmo for ScaleSubGizmo {
             (TransformKind::Plane, _) => draw_plane(self, ui, self.direction),
         }
     }
+
+    fn screen_bounds(&self) -> Rect {
+        match (self.transform_kind, self.direction) {
+            (TransformKind::Axis, _) => {
+                arrow_bounds(&self.config, self.direction, ArrowheadStyle::Square)
+            }
+            (TransformKind::Plane, GizmoDirection::View) => {
+                circle_bounds(&self.config, outer_circle_radius(&self.config))
+            }
+            (TransformKind::Plane, _) => plane_bounds(&self.config, self.direction),
+        }
+    }
+
+    fn handle(&self) -> GizmoHandle {
+        GizmoHandle {
+            mode: GizmoMode::Scale,
+            direction: self.direction,
+            transform_kind: Some(self.transform_kind),
+        }
+    }
+}
+
+fn pick_scale(subgizmo: &SubGizmoConfig<Scale>, ray: Ray) -> PickResult {
+    match (subgizmo.transform_kind, subgizmo.direction) {
+        (TransformKind::Plane, GizmoDirection::View) => {
+            let mut result =
+                pick_circle(subgizmo, ray, inner_circle_radius(&subgizmo.config), true);
+            if !result.picked {
+                result = pick_circle(subgizmo, ray, outer_circle_radius(&subgizmo.config), false);
+            }
+            result
+        }
+        (TransformKind::Plane, _) => pick_plane(subgizmo, ray, subgizmo.direction),
+        (TransformKind::Axis, _) => {
+            pick_arrow(subgizmo, ray, subgizmo.direction, ArrowheadStyle::Square)
+        }
+    }
+}
+
+/// Screen position of the gizmo origin
+fn origin_screen_pos<T: SubGizmoKind>(subgizmo: &SubGizmoConfig<T>) -> Option<Pos2> {
+    world_to_screen(subgizmo.config.viewport, subgizmo.config.mvp, DVec3::ZERO)
 }
 
-fn distance_from_origin_2d<T: SubGizmoKind>(subgizmo: &SubGizmoConfig<T>, ui: &Ui) -> Option<f64> {
+fn distance_from_origin_2d(ui: &Ui, origin: Pos2) -> Option<f64> {
     let cursor_pos = ui.input(|i| i.pointer.hover_pos())?;
-    let viewport = subgizmo.config.viewport;
-    let gizmo_pos = world_to_screen(viewport, subgizmo.config.mvp, DVec3::new(0.0, 0.0, 0.0))?;
 
-    Some(cursor_pos.distance(gizmo_pos) as f64)
+    Some(cursor_pos.distance(origin) as f64)
 }
@@ -1,55 +1,70 @@
-use egui::Ui;
-use glam::DVec3;
+use std::cmp::Ordering;
+use std::rc::Rc;
 
-use crate::math::{intersect_plane, ray_to_ray, round_to_interval};
+use egui::{Rect, Ui};
+use glam::{DMat4, DQuat, DVec3, Vec3};
+
+use crate::math::{intersect_plane, ray_to_ray, round_to_interval, world_to_screen};
+use crate::painter::Painter3d;
 
 use crate::subgizmo::common::{
-    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_normal, inner_circle_radius,
-    pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_global_origin, plane_tangent,
-    ArrowheadStyle,
+    arrow_bounds, circle_bounds, draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_normal,
+    inner_circle_radius, pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_bounds,
+    plane_global_origin, plane_tangent, smooth, ArrowheadStyle,
+};
+use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind};
+use crate::{
+    GizmoDirection, GizmoHandle, GizmoMode, GizmoResult, PlacementFn, PlacementQuery, Ray, SnapFn,
+    SnapQuery, StrokeStyle, TransformKind,
 };
-use crate::subgizmo::{SubGizmo, SubGizmoConfig, SubGizmoKind, TransformKind};
-use crate::{GizmoDirection, GizmoMode, GizmoResult, Ray};
 
 pub(crate) type TranslationSubGizmo = SubGizmoConfig<Translation>;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Clone)]
 pub(crate) struct TranslationParams {
     pub direction: GizmoDirection,
     pub transform_kind: TransformKind,
+    pub snap_fn: Option<Rc<SnapFn>>,
+    pub placement_fn: Option<Rc<PlacementFn>>,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct TranslationState {
+    /// Translation of the gizmo when the drag started
+    start_translation: DVec3,
+    /// Start point of the drag, relative to `start_translation`
     start_point: DVec3,
-    last_point: DVec3,
     current_delta: DVec3,
+    /// Smoothed translation before snapping, see [`Gizmo::smoothing`](crate::Gizmo::smoothing)
+    smoothed_delta: DVec3,
 }
 
 #[derive(Default, Debug, Copy, Clone)]
 pub(crate) struct Translation;
 
 impl SubGizmoKind for Translation {
+    const MODE: GizmoMode = GizmoMode::Translate;
     type Params = TranslationParams;
     type State = TranslationState;
 }
 
 impl SubGizmo for TranslationSubGizmo {
-    fn pick(&mut self, ui: &Ui, ray: Ray) -> Option<f64> {
+    fn pick(&mut self, _ui: &Ui, ray: Ray) -> Option<f64> {
         let pick_result = match (self.transform_kind, self.direction) {
             (TransformKind::Plane, GizmoDirection::View) => {
                 pick_circle(self, ray, inner_circle_radius(&self.config), true)
             }
             (TransformKind::Plane, _) => pick_plane(self, ray, self.direction),
-            (TransformKind::Axis, _) => pick_arrow(self, ray, self.direction),
+            (TransformKind::Axis, _) => pick_arrow(self, ray, self.direction, ArrowheadStyle::Cone),
         };
 
-        self.opacity = pick_result.visibility as _;
+        self.interaction_point = Some(pick_result.subgizmo_point);
 
-        self.update_state_with(ui, |state: &mut TranslationState| {
-            state.start_point = pick_result.subgizmo_point;
-            state.last_point = pick_result.subgizmo_point;
+        self.update_state_with(|state: &mut TranslationState| {
+            state.start_translation = self.config.translation;
+            state.start_point = pick_result.subgizmo_point - self.config.translation;
             state.current_delta = DVec3::ZERO;
+            state.smoothed_delta = DVec3::ZERO;
         });
 
         if pick_result.picked {
@@ -59,47 +74,141 @@ impl SubGizmo for TranslationSubGizmo {
         }
     }
 
-    fn update(&mut self, ui: &Ui, ray: Ray) -> Option<GizmoResult> {
-        let state = self.state(ui);
+    fn update(&mut self, _ui: &Ui, ray: Ray) -> Option<GizmoResult> {
+        let state = self.state();
+
+        if let Some(result) = self.place(state, ray) {
+            return Some(result);
+        }
+
+        // Interaction math is done relative to the translation at the start of the drag
+        // to avoid losing precision when the gizmo is far away from the world origin.
+        let ray = ray.rebased(state.start_translation);
 
-        let mut new_point = if self.transform_kind == TransformKind::Axis {
+        let new_point = if self.transform_kind == TransformKind::Axis {
             point_on_axis(self, ray)
         } else {
             point_on_plane(
                 gizmo_normal(&self.config, self.direction),
-                plane_global_origin(&self.config, self.direction),
+                plane_global_origin(&self.config, self.direction) - self.config.translation,
                 ray,
             )?
         };
 
-        let mut new_delta = new_point - state.start_point;
+        self.interaction_point = Some(state.start_translation + new_point);
+
+        let raw_delta = smooth(
+            &self.config,
+            state.smoothed_delta,
+            new_point - state.start_point,
+        );
+        let mut new_delta = raw_delta;
+        let mut snap_increment = None;
 
         if self.config.snapping {
-            new_delta = if self.transform_kind == TransformKind::Axis {
-                snap_translation_vector(self, new_delta)
+            let snap_point = self.snap_fn.as_ref().and_then(|snap_fn| {
+                snap_fn(SnapQuery {
+                    position: (state.start_translation + new_delta).as_vec3().into(),
+                    direction: self.direction,
+                    transform_kind: self.transform_kind,
+                    ray_origin: (state.start_translation + ray.origin).as_vec3().into(),
+                    ray_direction: ray.direction.as_vec3().into(),
+                })
+            });
+
+            new_delta = if let Some(snap_point) = snap_point {
+                let snap_delta = Vec3::from(snap_point).as_dvec3() - state.start_translation;
+                constrain_translation(self, snap_delta)
             } else {
-                snap_translation_plane(self, new_delta)
+                snap_increment = Some(self.config.snap_distance);
+                if self.transform_kind == TransformKind::Axis {
+                    snap_translation_vector(self, new_delta)
+                } else {
+                    snap_translation_plane(self, new_delta)
+                }
             };
-            new_point = state.start_point + new_delta;
         }
 
-        self.update_state_with(ui, |state: &mut TranslationState| {
-            state.last_point = new_point;
+        self.update_state_with(|state: &mut TranslationState| {
             state.current_delta = new_delta;
+            state.smoothed_delta = raw_delta;
         });
 
-        let new_translation = self.config.translation + new_point - state.last_point;
+        let new_translation = state.start_translation + new_delta;
 
         Some(GizmoResult {
-            scale: self.config.scale.as_vec3().into(),
-            rotation: self.config.rotation.as_quat().into(),
-            translation: new_translation.as_vec3().into(),
             mode: GizmoMode::Translate,
-            value: Some(state.current_delta.as_vec3().to_array()),
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
+            value: Some(new_delta.as_vec3().to_array()),
+            raw_value: Some(raw_delta.as_vec3().to_array()),
+            snapped: self.config.snapping,
+            snap_increment,
+            ..GizmoResult::from_transform(self.config.scale, self.config.rotation, new_translation)
+        })
+    }
+
+    fn reset(&self) -> GizmoResult {
+        let normal = gizmo_normal(&self.config, self.direction);
+        let translation = self.config.translation;
+
+        let new_translation = match (self.transform_kind, self.direction) {
+            (TransformKind::Axis, _) => translation - normal * translation.dot(normal),
+            (TransformKind::Plane, GizmoDirection::View) => DVec3::ZERO,
+            (TransformKind::Plane, _) => normal * translation.dot(normal),
+        };
+
+        GizmoResult {
+            mode: GizmoMode::Translate,
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
+            reset: true,
+            ..GizmoResult::from_transform(self.config.scale, self.config.rotation, new_translation)
+        }
+    }
+
+    fn nudge(&self, steps: i32) -> Option<GizmoResult> {
+        if self.transform_kind != TransformKind::Axis {
+            return None;
+        }
+
+        let normal = gizmo_normal(&self.config, self.direction);
+        let delta = normal * steps as f64 * self.config.snap_distance as f64;
+
+        Some(GizmoResult {
+            mode: GizmoMode::Translate,
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
+            value: Some(delta.as_vec3().to_array()),
+            ..GizmoResult::from_transform(
+                self.config.scale,
+                self.config.rotation,
+                self.config.translation + delta,
+            )
+        })
+    }
+
+    fn value(&self) -> Option<f64> {
+        (self.transform_kind == TransformKind::Axis).then(|| {
+            self.config
+                .translation
+                .dot(gizmo_normal(&self.config, self.direction))
         })
     }
 
     fn draw(&mut self, ui: &Ui) {
+        if self.active && self.config.snapping && self.config.visuals.show_snap_grid {
+            draw_snap_grid(self, ui);
+        }
+
+        if self.active
+            && self.config.snapping
+            && self.config.visuals.show_snap_ruler
+            && self.transform_kind == TransformKind::Axis
+        {
+            draw_snap_ruler(self, ui);
+        }
+
         match (self.transform_kind, self.direction) {
             (TransformKind::Axis, _) => draw_arrow(self, ui, self.direction, ArrowheadStyle::Cone),
             (TransformKind::Plane, GizmoDirection::View) => {
@@ -114,16 +223,199 @@ impl SubGizmo for TranslationSubGizmo {
             (TransformKind::Plane, _) => draw_plane(self, ui, self.direction),
         }
     }
+
+    fn screen_bounds(&self) -> Rect {
+        match (self.transform_kind, self.direction) {
+            (TransformKind::Axis, _) => {
+                arrow_bounds(&self.config, self.direction, ArrowheadStyle::Cone)
+            }
+            (TransformKind::Plane, GizmoDirection::View) => {
+                circle_bounds(&self.config, inner_circle_radius(&self.config))
+            }
+            (TransformKind::Plane, _) => plane_bounds(&self.config, self.direction),
+        }
+    }
+
+    fn handle(&self) -> GizmoHandle {
+        GizmoHandle {
+            mode: GizmoMode::Translate,
+            direction: self.direction,
+            transform_kind: Some(self.transform_kind),
+        }
+    }
 }
 
-/// Finds the nearest point on line that points in translation subgizmo direction
+/// Draws a grid of the snapping targets around the start point of the drag
+fn draw_snap_grid(subgizmo: &SubGizmoConfig<Translation>, ui: &Ui) {
+    let state = subgizmo.state();
+    let config = &subgizmo.config;
+
+    let (u, v) = snap_grid_axes(subgizmo);
+
+    let painter = Painter3d::new(
+        ui.painter().clone(),
+        config.view_projection * DMat4::from_translation(state.start_translation),
+        config.viewport,
+    );
+
+    let color = gizmo_color(subgizmo, subgizmo.direction);
+    let stroke_width = config.visuals.stroke_width / 4.0;
+    let style = config.visuals.guide_stroke_style;
+    let spacing = config.snap_distance as f64;
+    let half_size = (config.visuals.snap_grid_size / 2).max(1) as i32;
+
+    // Lines are drawn in segments, which fade out towards the edges of the grid
+    for i in -half_size..=half_size {
+        for j in -half_size..half_size {
+            let distance = i.abs().max(j.abs().max((j + 1).abs())) as f32;
+            let alpha = 1.0 - distance / (half_size as f32 + 1.0);
+            let stroke = (stroke_width, color.gamma_multiply(alpha));
+
+            let line = i as f64 * spacing;
+            let start = j as f64 * spacing;
+            let end = start + spacing;
+
+            painter.line_segment(u * line + v * start, u * line + v * end, stroke, style);
+            painter.line_segment(v * line + u * start, v * line + u * end, stroke, style);
+        }
+    }
+}
+
+/// Draws tick marks of the snapping targets along the axis of the subgizmo
+fn draw_snap_ruler(subgizmo: &SubGizmoConfig<Translation>, ui: &Ui) {
+    const MAX_TICKS: f64 = 1000.0;
+    const MAJOR_TICK_INTERVAL: i32 = 5;
+
+    let state = subgizmo.state();
+    let config = &subgizmo.config;
+
+    let painter = Painter3d::new(
+        ui.painter().clone(),
+        config.view_projection * DMat4::from_translation(state.start_translation),
+        config.viewport,
+    );
+
+    let axis = gizmo_normal(config, subgizmo.direction);
+    let tick_direction = axis.cross(config.view_forward()).normalize_or_zero();
+    let tick_length = (config.scale_factor * config.visuals.stroke_width * 2.0) as f64;
+    let spacing = config.snap_distance as f64;
+
+    // Approximate length of the viewport in world units at the depth of the gizmo
+    let viewport_length = (config.scale_factor * config.viewport.size().length()) as f64;
+    let tick_count = (viewport_length / spacing).ceil().min(MAX_TICKS) as i32;
+
+    let color = gizmo_color(subgizmo, subgizmo.direction);
+    let stroke = (config.visuals.stroke_width / 2.0, color);
+
+    for i in -tick_count..=tick_count {
+        let point = axis * (i as f64 * spacing);
+
+        let visible = world_to_screen(config.viewport, painter.mvp(), point)
+            .filter(|pos| config.viewport.contains(*pos))
+            .is_some();
+        if !visible {
+            continue;
+        }
+
+        let length = if i % MAJOR_TICK_INTERVAL == 0 {
+            tick_length * 2.0
+        } else {
+            tick_length
+        };
+
+        painter.line_segment(
+            point,
+            point + tick_direction * length,
+            stroke,
+            StrokeStyle::Solid,
+        );
+    }
+}
+
+/// Axes of the plane on which the snap grid is drawn
+fn snap_grid_axes(subgizmo: &SubGizmoConfig<Translation>) -> (DVec3, DVec3) {
+    let config = &subgizmo.config;
+
+    let rotate = |axis: DVec3| {
+        if config.local_space() {
+            config.rotation * axis
+        } else {
+            axis
+        }
+    };
+
+    match (subgizmo.transform_kind, subgizmo.direction) {
+        (TransformKind::Plane, GizmoDirection::View) => (config.view_right(), config.view_up()),
+        (TransformKind::Plane, direction) => (
+            rotate(plane_tangent(direction)),
+            rotate(plane_bitangent(direction)),
+        ),
+        (TransformKind::Axis, direction) => {
+            // Use the plane containing the axis that faces the camera the most
+            let axis = gizmo_normal(config, direction);
+            let forward = config.view_forward();
+            let other = [plane_tangent(direction), plane_bitangent(direction)]
+                .map(rotate)
+                .into_iter()
+                .max_by(|a, b| {
+                    let a = axis.cross(*a).dot(forward).abs();
+                    let b = axis.cross(*b).dot(forward).abs();
+                    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                })
+                .unwrap_or(DVec3::ZERO);
+            (axis, other)
+        }
+    }
+}
+
+impl TranslationSubGizmo {
+    /// Places the object on the point given by the placement function, when the
+    /// placement modifier is held
+    fn place(&mut self, state: TranslationState, ray: Ray) -> Option<GizmoResult> {
+        if !self.config.placing {
+            return None;
+        }
+        let placement_fn = self.placement_fn.as_ref()?;
+        let (position, normal) = placement_fn(PlacementQuery {
+            start_position: state.start_translation.as_vec3().into(),
+            ray_origin: ray.origin.as_vec3().into(),
+            ray_direction: ray.direction.as_vec3().into(),
+        })?;
+        let position = Vec3::from(position).as_dvec3();
+        let normal = Vec3::from(normal).as_dvec3().try_normalize();
+
+        let rotation = match normal {
+            Some(normal) if self.config.align_to_normal => {
+                let up = self.config.rotation * DVec3::Y;
+                (DQuat::from_rotation_arc(up, normal) * self.config.rotation).normalize()
+            }
+            _ => self.config.rotation,
+        };
+
+        self.interaction_point = Some(position);
+        let delta = position - state.start_translation;
+        self.update_state_with(|state: &mut TranslationState| {
+            state.current_delta = delta;
+        });
+
+        Some(GizmoResult {
+            mode: GizmoMode::Translate,
+            direction: Some(self.direction),
+            transform_kind: Some(self.transform_kind),
+            value: Some(delta.as_vec3().to_array()),
+            ..GizmoResult::from_transform(self.config.scale, rotation, position)
+        })
+    }
+}
+
+/// Finds the nearest point on line that points in translation subgizmo direction.
+/// The line passes through the origin, so the ray should be relative to the gizmo.
 fn point_on_axis(subgizmo: &SubGizmoConfig<Translation>, ray: Ray) -> DVec3 {
-    let origin = subgizmo.config.translation;
     let direction = gizmo_normal(&subgizmo.config, subgizmo.direction);
 
-    let (_ray_t, subgizmo_t) = ray_to_ray(ray.origin, ray.direction, origin, direction);
+    let (_ray_t, subgizmo_t) = ray_to_ray(ray.origin, ray.direction, DVec3::ZERO, direction);
 
-    origin + direction * subgizmo_t
+    direction * subgizmo_t
 }
 
 fn point_on_plane(plane_normal: DVec3, plane_origin: DVec3, ray: Ray) -> Option<DVec3> {
@@ -141,6 +433,17 @@ fn point_on_plane(plane_normal: DVec3, plane_origin: DVec3, ray: Ray) -> Option<
     }
 }
 
+/// Projects the given translation delta onto the axis or plane of the subgizmo
+fn constrain_translation(subgizmo: &SubGizmoConfig<Translation>, delta: DVec3) -> DVec3 {
+    let normal = gizmo_normal(&subgizmo.config, subgizmo.direction);
+
+    if subgizmo.transform_kind == TransformKind::Axis {
+        normal * delta.dot(normal)
+    } else {
+        delta - normal * delta.dot(normal)
+    }
+}
+
 fn snap_translation_vector(subgizmo: &SubGizmoConfig<Translation>, new_delta: DVec3) -> DVec3 {
     let delta_length = new_delta.length();
     if delta_length > 1e-5 {
@@ -0,0 +1,129 @@
+use egui::{Align2, FontId, Rect, Ui, Vec2};
+use glam::{DMat4, DVec3, Mat4};
+
+use crate::math::world_to_screen;
+use crate::painter::Painter3d;
+use crate::{is_left_handed, GizmoDirection, GizmoVisuals, StrokeStyle};
+
+/// Length of the axes relative to the radius of the triad
+const AXIS_LENGTH: f64 = 0.7;
+/// Distance of the axis labels from the center relative to the radius of the triad
+const LABEL_DISTANCE: f64 = 0.88;
+
+/// Small non-interactive axis triad showing the orientation of the camera,
+/// commonly placed in a corner of the viewport.
+///
+/// The axes are drawn from the rotation of the view matrix only, with the same
+/// colors and painter as the [`Gizmo`](crate::Gizmo).
+///
+/// ```text
+/// let viewport = ui.clip_rect();
+/// let corner = Rect::from_min_size(viewport.right_top() - vec2(80.0, 0.0), vec2(80.0, 80.0));
+///
+/// AxisTriad::new()
+///     .view_matrix(view_matrix)
+///     .size(80.0)
+///     .paint(ui, corner);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct AxisTriad {
+    view_matrix: DMat4,
+    left_handed: bool,
+    size: f32,
+    visuals: GizmoVisuals,
+}
+
+impl Default for AxisTriad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AxisTriad {
+    pub fn new() -> Self {
+        Self {
+            view_matrix: DMat4::IDENTITY,
+            left_handed: false,
+            size: 80.0,
+            visuals: GizmoVisuals::default(),
+        }
+    }
+
+    /// Matrix that specifies the transformation of the viewport camera.
+    /// Only its rotation is used.
+    pub fn view_matrix(mut self, view_matrix: impl Into<mint::ColumnMatrix4<f32>>) -> Self {
+        self.view_matrix = Mat4::from(view_matrix.into()).as_dmat4();
+        self
+    }
+
+    /// Projection matrix of the viewport camera. Only its handedness is used,
+    /// so that the triad matches the gizmo for left handed projections.
+    pub fn projection_matrix(
+        mut self,
+        projection_matrix: impl Into<mint::ColumnMatrix4<f32>>,
+    ) -> Self {
+        self.left_handed = is_left_handed(Mat4::from(projection_matrix.into()).as_dmat4());
+        self
+    }
+
+    /// Diameter of the triad in points
+    pub const fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Visuals of the triad. The axis colors, stroke width and inactive alpha are used.
+    pub const fn visuals(mut self, visuals: GizmoVisuals) -> Self {
+        self.visuals = visuals;
+        self
+    }
+
+    /// Paints the triad centered in the given rect
+    pub fn paint(self, ui: &Ui, rect: Rect) {
+        let viewport = Rect::from_center_size(rect.center(), Vec2::splat(self.size));
+        let rotation = DMat4::from_quat(self.view_matrix.to_scale_rotation_translation().1);
+        let projection = if self.left_handed {
+            DMat4::orthographic_lh(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0)
+        } else {
+            DMat4::orthographic_rh(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0)
+        };
+        let mvp = projection * rotation;
+
+        let painter = Painter3d::new(ui.painter().with_clip_rect(rect), mvp, viewport);
+
+        let mut axes = [
+            (GizmoDirection::X, self.visuals.x_color, "X"),
+            (GizmoDirection::Y, self.visuals.y_color, "Y"),
+            (GizmoDirection::Z, self.visuals.z_color, "Z"),
+        ]
+        .map(|(direction, color, label)| {
+            let axis = direction.axis().unwrap_or_default();
+            // Depth in normalized device coordinates, smaller is closer to the camera
+            let depth = mvp.project_point3(axis).z;
+            (axis, color, label, depth)
+        });
+        // Axes pointing towards the camera are drawn last
+        axes.sort_by(|a, b| b.3.total_cmp(&a.3));
+
+        let font = FontId::proportional(self.size * 0.14);
+        for (axis, color, label, _) in axes {
+            let color = color.gamma_multiply(self.visuals.inactive_alpha);
+            painter.line_segment(
+                DVec3::ZERO,
+                axis * AXIS_LENGTH,
+                (self.visuals.stroke_width, color),
+                StrokeStyle::Solid,
+            );
+
+            if let Some(pos) = world_to_screen(viewport, mvp, axis * LABEL_DISTANCE) {
+                ui.painter().with_clip_rect(rect).text(
+                    pos,
+                    Align2::CENTER_CENTER,
+                    label,
+                    font.clone(),
+                    color,
+                );
+            }
+        }
+    }
+}
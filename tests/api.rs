@@ -0,0 +1,73 @@
+//! Checks the trait implementations of the public types, so that they are not
+//! dropped accidentally.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use egui_gizmo::{
+    AngleUnit, ArcballBehavior, GizmoDebugInfo, GizmoDirection, GizmoError, GizmoHandle, GizmoMode,
+    GizmoOrientation, GizmoPlane, GizmoResult, GizmoVisuals, GizmoVisualsOverride, PlacementQuery,
+    PlanePlacement, ScaleOrigin, ScaleSnapMode, SnapQuery, StrokeStyle, TransformKind,
+};
+use glam::{Mat4, Quat, Vec3};
+
+fn value_type<T: Debug + Copy + Clone + PartialEq>() {}
+
+fn key_type<T: Debug + Copy + Clone + Eq + Hash>() {}
+
+#[test]
+fn public_types_implement_common_traits() {
+    value_type::<GizmoResult>();
+    value_type::<GizmoVisuals>();
+    value_type::<GizmoVisualsOverride>();
+    value_type::<GizmoDebugInfo>();
+    value_type::<SnapQuery>();
+    value_type::<PlacementQuery>();
+    value_type::<ScaleSnapMode>();
+    value_type::<StrokeStyle>();
+
+    key_type::<GizmoMode>();
+    key_type::<GizmoOrientation>();
+    key_type::<GizmoDirection>();
+    key_type::<GizmoPlane>();
+    key_type::<GizmoHandle>();
+    key_type::<GizmoError>();
+    key_type::<PlanePlacement>();
+    key_type::<TransformKind>();
+    key_type::<ScaleOrigin>();
+    key_type::<ArcballBehavior>();
+    key_type::<AngleUnit>();
+
+    let _: GizmoResult = Default::default();
+    let _: GizmoVisuals = Default::default();
+    let _: GizmoVisualsOverride = Default::default();
+}
+
+#[test]
+fn default_result_is_identity() {
+    let result = GizmoResult::default();
+    assert_eq!(Mat4::from(result.transform()), Mat4::IDENTITY);
+    assert_eq!(result, GizmoResult::default());
+}
+
+#[test]
+fn results_compare_approximately() {
+    let result = GizmoResult::default();
+    let mut other = result;
+    other.translation = Vec3::new(0.0, 1e-6, 0.0).into();
+
+    assert_ne!(result, other);
+    assert!(result.approx_eq(&other, 1e-5));
+    assert!(!result.approx_eq(&other, 1e-7));
+    assert!(result.approx_eq(&result, 0.0));
+}
+
+#[test]
+fn opposite_quaternions_compare_approximately() {
+    let mut result = GizmoResult::default();
+    result.rotation = Quat::from_rotation_y(1.0).into();
+    let mut other = result;
+    other.rotation = (-Quat::from_rotation_y(1.0)).into();
+
+    assert!(result.approx_eq(&other, 1e-6));
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "bevy")]
+
+use bevy_math::{Mat4, Quat, Vec3};
+use bevy_transform::components::{GlobalTransform, Transform};
+use egui::{CentralPanel, Context, Event, Frame, PointerButton, Pos2, RawInput, Rect, Vec2};
+use egui_gizmo::{Gizmo, GizmoMode};
+
+const SCREEN_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+
+fn run_frame(
+    ctx: &Context,
+    time: f64,
+    events: Vec<Event>,
+    transform: &Transform,
+) -> Option<Transform> {
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, SCREEN_SIZE)),
+        time: Some(time),
+        events,
+        ..Default::default()
+    };
+
+    let camera_transform =
+        GlobalTransform::from(Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y));
+    let projection_matrix = Mat4::perspective_infinite_reverse_rh(
+        45_f32.to_radians(),
+        SCREEN_SIZE.x / SCREEN_SIZE.y,
+        0.1,
+    );
+
+    let mut result = None;
+    let _ = ctx.run(input, |ctx| {
+        CentralPanel::default()
+            .frame(Frame::none())
+            .show(ctx, |ui| {
+                result = Gizmo::new("gizmo")
+                    .bevy_camera(&camera_transform, projection_matrix)
+                    .bevy_transform(transform)
+                    .mode(GizmoMode::Translate)
+                    .interact(ui)
+                    .map(|result| result.to_bevy_transform());
+            });
+    });
+    result
+}
+
+fn pointer_button(pos: Pos2, pressed: bool) -> Event {
+    Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    }
+}
+
+#[test]
+fn transform_round_trips_through_noop_drag() {
+    let ctx = Context::default();
+    let transform = Transform {
+        translation: Vec3::ZERO,
+        rotation: Quat::from_euler(bevy_math::EulerRot::XYZ, 0.3, -0.7, 1.1),
+        scale: Vec3::new(1.0, 2.0, 0.5),
+    };
+
+    let center = (SCREEN_SIZE / 2.0).to_pos2();
+    let away = center + Vec2::new(40.0, 0.0);
+
+    let frames = [
+        vec![Event::PointerMoved(center)],
+        vec![pointer_button(center, true)],
+        vec![Event::PointerMoved(away)],
+        vec![Event::PointerMoved(center)],
+    ];
+
+    let mut last = None;
+    for (i, events) in frames.into_iter().enumerate() {
+        last = run_frame(&ctx, i as f64 * 0.1, events, &transform).or(last);
+    }
+    let _ = run_frame(&ctx, 0.4, vec![pointer_button(center, false)], &transform);
+
+    let result = last.expect("dragging the gizmo should produce a result");
+
+    assert!(result.translation.abs_diff_eq(transform.translation, 1e-4));
+    assert!(result.rotation.abs_diff_eq(transform.rotation, 1e-4));
+    assert!(result.scale.abs_diff_eq(transform.scale, 1e-4));
+}
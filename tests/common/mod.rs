@@ -0,0 +1,243 @@
+//! Drives the gizmo with simulated input, one egui frame at a time
+
+#![allow(dead_code)]
+
+use egui::epaint::ClippedShape;
+use egui::{
+    CentralPanel, Context, Event, Frame, Modifiers, PointerButton, Pos2, RawInput, Rect, Shape,
+    TouchDeviceId, TouchId, TouchPhase, Ui, Vec2,
+};
+use egui_gizmo::{Gizmo, GizmoError, GizmoInteraction, GizmoResult};
+use glam::{Mat4, Vec3};
+
+pub const SCREEN_SIZE: Vec2 = Vec2::new(800.0, 600.0);
+
+/// Size of the gizmo in pixels with the default visuals
+pub const GIZMO_SIZE: f32 = 75.0;
+
+pub struct Harness {
+    ctx: Context,
+    view: Mat4,
+    projection: Mat4,
+    viewport: Rect,
+    modifiers: Modifiers,
+    time: f64,
+    shapes: Vec<ClippedShape>,
+}
+
+impl Harness {
+    /// Harness with a perspective camera and a viewport covering the whole screen
+    pub fn new(view: Mat4) -> Self {
+        Self::with_viewport(view, Rect::from_min_size(Pos2::ZERO, SCREEN_SIZE))
+    }
+
+    /// Harness with a perspective camera and the given viewport
+    pub fn with_viewport(view: Mat4, viewport: Rect) -> Self {
+        Self {
+            ctx: Context::default(),
+            view,
+            projection: Mat4::perspective_rh(
+                45_f32.to_radians(),
+                viewport.aspect_ratio(),
+                0.1,
+                100.0,
+            ),
+            viewport,
+            modifiers: Modifiers::NONE,
+            time: 0.0,
+            shapes: Vec::new(),
+        }
+    }
+
+    /// Harness with the given camera matrices and a viewport covering the whole screen
+    pub fn with_projection(view: Mat4, projection: Mat4) -> Self {
+        Self {
+            projection,
+            ..Self::new(view)
+        }
+    }
+
+    /// Harness with a camera at (0, 0, 10) looking at the origin
+    pub fn front() -> Self {
+        Self::new(Mat4::look_at_rh(
+            Vec3::new(0.0, 0.0, 10.0),
+            Vec3::ZERO,
+            Vec3::Y,
+        ))
+    }
+
+    /// Screen position of the center of the viewport
+    pub fn center(&self) -> Pos2 {
+        self.viewport.center()
+    }
+
+    /// Viewport of the gizmo
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Runs a single frame with the given input events
+    pub fn frame(&mut self, events: Vec<Event>, gizmo: Gizmo) -> GizmoInteraction {
+        self.frame_with(events, gizmo, |_| {})
+    }
+
+    /// Runs a single frame with the given input events, with widgets added by `behind`
+    /// before the gizmo. Widgets added earlier are behind the later ones in egui.
+    pub fn frame_with(
+        &mut self,
+        events: Vec<Event>,
+        gizmo: Gizmo,
+        behind: impl FnOnce(&mut Ui),
+    ) -> GizmoInteraction {
+        let gizmo = self.with_camera(gizmo);
+        self.run(events, gizmo, behind, Gizmo::interact_detailed)
+    }
+
+    /// Runs a single frame with the given input events, where the gizmo is given
+    /// its own view and projection matrices instead of those of the harness
+    pub fn frame_with_own_camera(&mut self, events: Vec<Event>, gizmo: Gizmo) -> GizmoInteraction {
+        let gizmo = gizmo.viewport(self.viewport);
+        self.run(events, gizmo, |_| {}, Gizmo::interact_detailed)
+    }
+
+    /// View and projection matrices of the harness
+    pub fn camera(&self) -> (Mat4, Mat4) {
+        (self.view, self.projection)
+    }
+
+    /// Runs a single frame with the given input events, returning configuration errors
+    pub fn try_frame(
+        &mut self,
+        events: Vec<Event>,
+        gizmo: Gizmo,
+    ) -> Result<GizmoInteraction, GizmoError> {
+        let gizmo = self.with_camera(gizmo);
+        self.run(events, gizmo, |_| {}, Gizmo::try_interact_detailed)
+    }
+
+    fn run<T>(
+        &mut self,
+        events: Vec<Event>,
+        gizmo: Gizmo,
+        behind: impl FnOnce(&mut Ui),
+        interact: impl FnOnce(Gizmo, &mut Ui) -> T,
+    ) -> T {
+        let input = RawInput {
+            // The screen grows to contain viewports larger than the default screen
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, SCREEN_SIZE).union(self.viewport)),
+            time: Some(self.time),
+            modifiers: self.modifiers,
+            events,
+            ..Default::default()
+        };
+        self.time += 0.1;
+
+        let mut interaction = None;
+        let output = self.ctx.run(input, |ctx| {
+            CentralPanel::default()
+                .frame(Frame::none())
+                .show(ctx, |ui| {
+                    behind(ui);
+                    interaction = Some(interact(gizmo, ui));
+                });
+        });
+        self.shapes = output.shapes;
+        interaction.expect("the gizmo should be shown in the frame")
+    }
+
+    fn with_camera(&self, gizmo: Gizmo) -> Gizmo {
+        gizmo
+            .view_matrix(self.view.to_cols_array_2d())
+            .projection_matrix(self.projection.to_cols_array_2d())
+            .viewport(self.viewport)
+    }
+
+    /// Shapes painted on the last frame
+    pub fn shapes(&self) -> impl Iterator<Item = &Shape> {
+        self.shapes.iter().map(|clipped| &clipped.shape)
+    }
+
+    /// Sets the modifier keys held on the following frames
+    pub fn set_modifiers(&mut self, modifiers: Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    /// Sets the egui style used for the following frames
+    pub fn set_style(&self, style: egui::Style) {
+        self.ctx.set_style(style);
+    }
+
+    /// Forgets the input state, such as pressed buttons, while keeping the memory.
+    /// Simulates input events that are lost, e.g. when the window loses the focus.
+    pub fn lose_input(&mut self) {
+        let memory = self.ctx.memory(|mem| mem.clone());
+        self.ctx = Context::default();
+        self.ctx.memory_mut(|mem| *mem = memory);
+    }
+
+    /// Drags from `from` through `to` and returns the last result of the drag
+    pub fn drag(&mut self, from: Pos2, to: Pos2, gizmo: impl Fn() -> Gizmo) -> Option<GizmoResult> {
+        let mut result = None;
+        for events in [
+            vec![Event::PointerMoved(from)],
+            vec![pointer_button(from, true)],
+            vec![Event::PointerMoved(from.lerp(to, 0.5))],
+            vec![Event::PointerMoved(to)],
+        ] {
+            result = self.frame(events, gizmo()).result.or(result);
+        }
+        let _ = self.frame(vec![pointer_button(to, false)], gizmo());
+        result
+    }
+
+    /// Touches with two fingers at `from`, moves them through the halfway point to `to`
+    /// and lifts them, returning the results of the gesture. The pointer is first moved
+    /// to the center of the touches, as egui starts gestures at the pointer.
+    pub fn touch(
+        &mut self,
+        from: [Pos2; 2],
+        to: [Pos2; 2],
+        gizmo: impl Fn() -> Gizmo,
+    ) -> Vec<GizmoResult> {
+        let halfway = [from[0].lerp(to[0], 0.5), from[1].lerp(to[1], 0.5)];
+        let mut results = Vec::new();
+        for events in [
+            vec![Event::PointerMoved(from[0].lerp(from[1], 0.5))],
+            touches(TouchPhase::Start, from),
+            touches(TouchPhase::Move, halfway),
+            touches(TouchPhase::Move, to),
+            touches(TouchPhase::End, to),
+        ] {
+            results.extend(self.frame(events, gizmo()).result);
+        }
+        results
+    }
+}
+
+/// Events of two touches with the given phase
+fn touches(phase: TouchPhase, positions: [Pos2; 2]) -> Vec<Event> {
+    (0..)
+        .zip(positions)
+        .map(|(id, pos)| Event::Touch {
+            device_id: TouchDeviceId(0),
+            id: TouchId(id),
+            phase,
+            pos,
+            force: None,
+        })
+        .collect()
+}
+
+pub fn pointer_button(pos: Pos2, pressed: bool) -> Event {
+    Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    }
+}
+
+/// Name of the subgizmo that produced the result
+pub fn handle_name(result: &GizmoResult) -> Option<String> {
+    result.handle().map(|handle| handle.to_string())
+}
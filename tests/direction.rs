@@ -0,0 +1,39 @@
+use std::f32::consts::FRAC_PI_2;
+
+use egui_gizmo::{GizmoDirection, GizmoOrientation};
+use glam::{Quat, Vec3};
+
+#[test]
+fn direction_round_trips_through_vector() {
+    for direction in [GizmoDirection::X, GizmoDirection::Y, GizmoDirection::Z] {
+        let vec = direction
+            .to_vec3(GizmoOrientation::Global, Quat::IDENTITY)
+            .expect("axis directions have a vector");
+        assert_eq!(GizmoDirection::from_vec3(vec), Some(direction));
+    }
+
+    assert!(GizmoDirection::View
+        .to_vec3(GizmoOrientation::Global, Quat::IDENTITY)
+        .is_none());
+    assert!(GizmoDirection::from_vec3(Vec3::new(1.0, 1.0, 0.0)).is_none());
+    assert!(GizmoDirection::from_vec3(Vec3::NEG_X).is_none());
+}
+
+#[test]
+fn local_direction_follows_rotation() {
+    let rotation = Quat::from_rotation_z(FRAC_PI_2);
+
+    let global = Vec3::from(
+        GizmoDirection::X
+            .to_vec3(GizmoOrientation::Global, rotation)
+            .unwrap(),
+    );
+    let local = Vec3::from(
+        GizmoDirection::X
+            .to_vec3(GizmoOrientation::Local, rotation)
+            .unwrap(),
+    );
+
+    assert_eq!(global, Vec3::X);
+    assert!(local.abs_diff_eq(Vec3::Y, 1e-6));
+}
@@ -0,0 +1,256 @@
+use egui::{Event, Pos2, Shape, Vec2};
+use egui_gizmo::{Gizmo, GizmoMode, GizmoOrientation, GizmoResult};
+use glam::{Mat4, Quat, Vec3};
+
+use common::{handle_name, pointer_button, Harness, GIZMO_SIZE};
+
+mod common;
+
+const EYE: Vec3 = Vec3::new(4.0, 3.0, 8.0);
+
+#[derive(Copy, Clone)]
+enum Projection {
+    Perspective,
+    Orthographic,
+}
+
+/// A right handed camera. The scene seen by the left handed camera at the
+/// same position is the mirror image of it, mirrored horizontally on the screen.
+fn right_handed(projection: Projection) -> Harness {
+    Harness::with_projection(
+        Mat4::look_at_rh(EYE, Vec3::ZERO, Vec3::Y),
+        match projection {
+            Projection::Perspective => {
+                Mat4::perspective_rh(45_f32.to_radians(), 4.0 / 3.0, 0.1, 100.0)
+            }
+            Projection::Orthographic => Mat4::orthographic_rh(-4.0, 4.0, -3.0, 3.0, 0.1, 100.0),
+        },
+    )
+}
+
+fn left_handed(projection: Projection) -> Harness {
+    Harness::with_projection(
+        Mat4::look_at_lh(EYE, Vec3::ZERO, Vec3::Y),
+        match projection {
+            Projection::Perspective => {
+                Mat4::perspective_lh(45_f32.to_radians(), 4.0 / 3.0, 0.1, 100.0)
+            }
+            Projection::Orthographic => Mat4::orthographic_lh(-4.0, 4.0, -3.0, 3.0, 0.1, 100.0),
+        },
+    )
+}
+
+fn mirror(harness: &Harness, pos: Pos2) -> Pos2 {
+    Pos2::new(2.0 * harness.center().x - pos.x, pos.y)
+}
+
+/// Screen position of a point on each subgizmo, found by hovering around the gizmo
+fn handle_positions(projection: Projection, gizmo: impl Fn() -> Gizmo) -> Vec<(String, Pos2)> {
+    let mut harness = right_handed(projection);
+    let center = harness.center();
+    let mut positions: Vec<(String, Pos2)> = Vec::new();
+
+    let extent = (GIZMO_SIZE * 1.3) as i32;
+    for y in (-extent..=extent).step_by(6) {
+        for x in (-extent..=extent).step_by(6) {
+            let pos = center + Vec2::new(x as f32, y as f32);
+            let hovered = harness
+                .frame(vec![Event::PointerMoved(pos)], gizmo())
+                .hovered;
+            if let Some(name) = hovered.map(|handle| handle.to_string()) {
+                if positions.iter().all(|(other, _)| *other != name) {
+                    positions.push((name, pos));
+                }
+            }
+        }
+    }
+    positions
+}
+
+fn assert_same_result(name: &str, right: &GizmoResult, left: &GizmoResult) {
+    assert_eq!(handle_name(left).as_deref(), Some(name));
+    let (right_translation, left_translation) =
+        (Vec3::from(right.translation), Vec3::from(left.translation));
+    let (right_rotation, left_rotation) = (Quat::from(right.rotation), Quat::from(left.rotation));
+    let (right_scale, left_scale) = (Vec3::from(right.scale), Vec3::from(left.scale));
+    assert!(
+        right_translation.abs_diff_eq(left_translation, 1e-3),
+        "{name}: translation {left_translation} is not {right_translation}"
+    );
+    assert!(
+        right_rotation.dot(left_rotation).abs() > 1.0 - 1e-4,
+        "{name}: rotation {left_rotation} is not {right_rotation}"
+    );
+    assert!(
+        right_scale.abs_diff_eq(left_scale, 1e-3),
+        "{name}: scale {left_scale} is not {right_scale}"
+    );
+}
+
+/// Drags every subgizmo in the right and left handed scenes, with mirrored pointer
+/// movements, and checks that the transformations are the same.
+fn assert_handedness_independent(projection: Projection, gizmo: impl Fn() -> Gizmo + Copy) {
+    let positions = handle_positions(projection, gizmo);
+    assert!(!positions.is_empty());
+
+    for (name, from) in positions {
+        let to = from + Vec2::new(25.0, 15.0);
+
+        let mut harness = right_handed(projection);
+        let right = harness.drag(from, to, gizmo);
+        let mut harness = left_handed(projection);
+        let left = harness.drag(mirror(&harness, from), mirror(&harness, to), gizmo);
+
+        let (Some(right), Some(left)) = (right, left) else {
+            panic!("{name}: dragging produced no result");
+        };
+        assert_eq!(handle_name(&right).as_deref(), Some(name.as_str()));
+        assert_same_result(&name, &right, &left);
+    }
+}
+
+/// Gizmo of a rotated and scaled object in local orientation
+fn local_gizmo(mode: GizmoMode) -> Gizmo {
+    let model = Mat4::from_scale_rotation_translation(
+        Vec3::new(1.0, 2.0, 0.5),
+        Quat::from_euler(glam::EulerRot::XYZ, 0.3, -0.5, 0.8),
+        Vec3::ZERO,
+    );
+    Gizmo::new("gizmo")
+        .mode(mode)
+        .orientation(GizmoOrientation::Local)
+        .model_matrix(model.to_cols_array_2d())
+}
+
+#[test]
+fn translation_is_independent_of_handedness() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        assert_handedness_independent(projection, || {
+            Gizmo::new("gizmo").mode(GizmoMode::Translate)
+        });
+        assert_handedness_independent(projection, || local_gizmo(GizmoMode::Translate));
+    }
+}
+
+#[test]
+fn rotation_is_independent_of_handedness() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        assert_handedness_independent(projection, || Gizmo::new("gizmo").mode(GizmoMode::Rotate));
+        assert_handedness_independent(projection, || local_gizmo(GizmoMode::Rotate));
+    }
+}
+
+#[test]
+fn scale_is_independent_of_handedness() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        assert_handedness_independent(projection, || Gizmo::new("gizmo").mode(GizmoMode::Scale));
+        assert_handedness_independent(projection, || local_gizmo(GizmoMode::Scale));
+    }
+}
+
+#[test]
+fn arcball_is_independent_of_handedness() {
+    for projection in [Projection::Perspective, Projection::Orthographic] {
+        assert_handedness_independent(projection, || Gizmo::new("gizmo").mode(GizmoMode::Arcball));
+        assert_handedness_independent(projection, || local_gizmo(GizmoMode::Arcball));
+    }
+}
+
+/// Point on the z = 0 plane under the screen position
+fn point_on_xy_plane(harness: &Harness, pos: Pos2) -> Vec3 {
+    let (view, projection) = harness.camera();
+    let viewport = harness.viewport();
+    let ndc_x = (pos.x - viewport.center().x) / (viewport.width() / 2.0);
+    let ndc_y = (viewport.center().y - pos.y) / (viewport.height() / 2.0);
+    let inverse = (projection * view).inverse();
+    let near = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 0.0));
+    let far = inverse.project_point3(Vec3::new(ndc_x, ndc_y, 0.9));
+    near + (far - near) * (-near.z / (far.z - near.z))
+}
+
+fn screen_pos(harness: &Harness, point: Vec3) -> Pos2 {
+    let (view, projection) = harness.camera();
+    let viewport = harness.viewport();
+    let ndc = (projection * view).project_point3(point);
+    Pos2::new(
+        viewport.center().x + ndc.x * viewport.width() / 2.0,
+        viewport.center().y - ndc.y * viewport.height() / 2.0,
+    )
+}
+
+/// Angle between the directions from the center to the given points, in degrees
+fn screen_angle(center: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let (a, b) = (a - center, b - center);
+    a.y.atan2(a.x).to_degrees() - b.y.atan2(b.x).to_degrees()
+}
+
+#[test]
+fn rotation_readout_and_sweep_agree_with_the_rotation() {
+    let eye = Vec3::new(0.0, 0.0, 10.0);
+    let projection = |left_handed| {
+        if left_handed {
+            Harness::with_projection(
+                Mat4::look_at_lh(eye, Vec3::ZERO, Vec3::Y),
+                Mat4::perspective_lh(45_f32.to_radians(), 4.0 / 3.0, 0.1, 100.0),
+            )
+        } else {
+            Harness::with_projection(
+                Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y),
+                Mat4::perspective_rh(45_f32.to_radians(), 4.0 / 3.0, 0.1, 100.0),
+            )
+        }
+    };
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Rotate);
+
+    for left_handed in [false, true] {
+        let mut harness = projection(left_handed);
+        let center = harness.center();
+        // The Z ring faces the camera, and is dragged counterclockwise on the screen
+        let from = center + Vec2::new(GIZMO_SIZE, 0.0);
+        let to = center + Vec2::angled(-40_f32.to_radians()) * GIZMO_SIZE;
+
+        let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo());
+        let _ = harness.frame(vec![pointer_button(from, true)], gizmo());
+        let _ = harness.frame(vec![Event::PointerMoved(from.lerp(to, 0.5))], gizmo());
+        let result = harness
+            .frame(vec![Event::PointerMoved(to)], gizmo())
+            .result
+            .expect("dragging the ring should produce a result");
+        assert_eq!(handle_name(&result), Some("Rotate Z".to_owned()));
+
+        // The readout is the axis and angle of the applied rotation
+        let rotation = Quat::from(result.rotation);
+        let value = Vec3::from(result.value.expect("the rotation should have a value"));
+        let readout = Quat::from_scaled_axis(value);
+        assert!(
+            readout.abs_diff_eq(rotation, 1e-4) || readout.abs_diff_eq(-rotation, 1e-4),
+            "left handed {left_handed}: readout {value} does not match {rotation}"
+        );
+
+        // The sweep is drawn from the center to the start and end of the rotation
+        let sweep = harness
+            .shapes()
+            .find_map(|shape| match shape {
+                Shape::Path(path)
+                    if path.points.len() == 3 && path.points[1].distance(center) < 1.0 =>
+                {
+                    Some(path.points.clone())
+                }
+                _ => None,
+            })
+            .expect("the sweep of the rotation should be drawn");
+        let (start, end) = (sweep[0], sweep[2]);
+
+        let rotated = screen_pos(&harness, rotation * point_on_xy_plane(&harness, from));
+        for (name, angle) in [
+            ("start", screen_angle(center, start, from)),
+            ("end", screen_angle(center, end, to)),
+            ("rotated start", screen_angle(center, end, rotated)),
+        ] {
+            assert!(
+                angle.abs() < 2.0,
+                "left handed {left_handed}: the {name} of the sweep is off by {angle} degrees"
+            );
+        }
+    }
+}
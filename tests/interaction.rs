@@ -0,0 +1,1305 @@
+use std::f32::consts::FRAC_PI_2;
+
+use egui::{Color32, Event, Id, Modifiers, PointerButton, Pos2, Sense, Shape, Vec2};
+use egui_gizmo::{
+    Gizmo, GizmoDirection, GizmoMode, GizmoOrientation, GizmoPlane, GizmoVisuals,
+    GizmoVisualsOverride, PlacementQuery, ScaleOrigin, ScaleSnapMode, TransformKind,
+};
+use glam::{Mat4, Quat, Vec3};
+
+use common::{handle_name, pointer_button, Harness, GIZMO_SIZE, SCREEN_SIZE};
+
+mod common;
+
+#[test]
+fn hover_without_press_does_not_transform() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+
+    let interaction = harness.frame(
+        vec![Event::PointerMoved(pos)],
+        Gizmo::new("gizmo").mode(GizmoMode::Translate),
+    );
+
+    assert!(interaction.result.is_none());
+    assert_eq!(
+        interaction.hovered.map(|handle| handle.to_string()),
+        Some("Translate X".to_owned())
+    );
+    assert!(interaction.world_units_per_pixel > 0.0);
+}
+
+#[test]
+fn drag_within_threshold_does_not_transform() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .drag_threshold(5.0)
+    };
+
+    assert!(harness
+        .drag(from, from + Vec2::new(3.0, 3.0), gizmo)
+        .is_none());
+
+    let result = harness
+        .drag(from, from + Vec2::new(6.0, 0.0), gizmo)
+        .expect("dragging beyond the threshold should produce a result");
+    assert_eq!(handle_name(&result), Some("Translate X".to_owned()));
+}
+
+#[test]
+fn click_reports_clicked_handle() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    let _ = harness.frame(vec![Event::PointerMoved(pos)], gizmo());
+    let pressed = harness.frame(vec![pointer_button(pos, true)], gizmo());
+    assert!(pressed.clicked.is_none());
+
+    let released = harness.frame(vec![pointer_button(pos, false)], gizmo());
+    assert!(released.result.is_none());
+    assert_eq!(
+        released.clicked.map(|handle| handle.to_string()),
+        Some("Translate X".to_owned())
+    );
+}
+
+#[test]
+fn drag_is_not_a_click() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(20.0, 0.0);
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    for events in [
+        vec![Event::PointerMoved(from)],
+        vec![pointer_button(from, true)],
+        vec![Event::PointerMoved(to)],
+        vec![pointer_button(to, false)],
+    ] {
+        assert!(harness.frame(events, gizmo()).clicked.is_none());
+    }
+}
+
+#[test]
+fn scrolling_nudges_hovered_handle() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(0.0, -GIZMO_SIZE * 0.6);
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .scroll_nudges(true)
+            .snap_distance(0.5)
+    };
+
+    let _ = harness.frame(vec![Event::PointerMoved(pos)], gizmo());
+
+    // Fine trackpad scrolling accumulates to a single step
+    let results = (0..5)
+        .filter_map(|_| {
+            harness
+                .frame(vec![Event::Scroll(Vec2::new(0.0, 10.0))], gizmo())
+                .result
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(handle_name(&results[0]), Some("Translate Y".to_owned()));
+    assert!(Vec3::from(results[0].translation).abs_diff_eq(Vec3::Y * 0.5, 1e-5));
+}
+
+#[test]
+fn scrolling_nudges_scale_in_snap_steps() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let mut nudged_scale = |mode: ScaleSnapMode| {
+        let gizmo = || {
+            Gizmo::new("gizmo")
+                .mode(GizmoMode::Scale)
+                .scroll_nudges(true)
+                .snap_scale(0.5)
+                .scale_snap_mode(mode)
+        };
+        let _ = harness.frame(vec![Event::PointerMoved(pos)], gizmo());
+        let result = harness
+            .frame(vec![Event::Scroll(Vec2::new(0.0, 50.0))], gizmo())
+            .result
+            .expect("scrolling over the X scale handle should nudge it");
+        result.scale.x
+    };
+
+    assert!((nudged_scale(ScaleSnapMode::Additive) - 1.5).abs() < 1e-5);
+    assert!((nudged_scale(ScaleSnapMode::Percent(10.0)) - 1.1).abs() < 1e-5);
+    assert!((nudged_scale(ScaleSnapMode::PowersOf(2.0)) - 2.0).abs() < 1e-5);
+    assert!((nudged_scale(ScaleSnapMode::PowersOf(0.5)) - 2.0).abs() < 1e-5);
+}
+
+#[test]
+fn missed_release_does_not_continue_drag() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    for events in [
+        vec![Event::PointerMoved(from)],
+        vec![pointer_button(from, true)],
+        vec![Event::PointerMoved(from + Vec2::new(20.0, 0.0))],
+    ] {
+        let _ = harness.frame(events, gizmo());
+    }
+
+    harness.lose_input();
+
+    // Pressing and dragging elsewhere must not move the gizmo
+    let elsewhere = harness.center() + Vec2::new(-200.0, 150.0);
+    for events in [
+        vec![Event::PointerGone],
+        vec![pointer_button(elsewhere, true)],
+        vec![Event::PointerMoved(elsewhere + Vec2::new(50.0, 0.0))],
+    ] {
+        assert!(harness.frame(events, gizmo()).result.is_none());
+    }
+}
+
+#[test]
+fn compact_gizmo_is_picked_once_expanded() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = |radius| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .visuals(GizmoVisuals::default().with_compact_when_idle(radius))
+    };
+
+    let compact = harness.frame(vec![Event::PointerMoved(pos)], gizmo(GIZMO_SIZE * 0.3));
+    assert!(compact.hovered.is_none());
+
+    let expanded = harness.frame(vec![Event::PointerMoved(pos)], gizmo(GIZMO_SIZE));
+    assert_eq!(
+        expanded.hovered.map(|handle| handle.to_string()),
+        Some("Translate X".to_owned())
+    );
+}
+
+#[test]
+fn faded_out_gizmo_is_not_picked() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = |near, far| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .visuals(GizmoVisuals::default().with_fade_distance(near, far))
+    };
+    let mut hovered = |near, far| {
+        harness
+            .frame(vec![Event::PointerMoved(pos)], gizmo(near, far))
+            .hovered
+            .map(|handle| handle.to_string())
+    };
+
+    // The camera is 10 units away from the gizmo
+    let translate_x = Some("Translate X".to_owned());
+    assert_eq!(hovered(15.0, f32::INFINITY), translate_x);
+    assert_eq!(hovered(25.0, f32::INFINITY), None);
+    assert_eq!(hovered(1.0, 6.0), translate_x);
+    assert_eq!(hovered(1.0, 4.0), None);
+}
+
+#[test]
+fn drags_outside_of_handles_reach_widgets_behind_gizmo() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let viewport = harness.viewport();
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+    let handle = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let empty = center + Vec2::new(GIZMO_SIZE * 2.0, GIZMO_SIZE * 1.5);
+
+    // Returns whether the gizmo transformed and whether the widget behind it was dragged
+    let mut drag = |from: Pos2, to: Pos2| {
+        let (mut transformed, mut selection_dragged) = (false, false);
+        for events in [
+            vec![Event::PointerMoved(handle)],
+            vec![pointer_button(from, true)],
+            vec![Event::PointerMoved(to)],
+            vec![pointer_button(to, false)],
+        ] {
+            let interaction = harness.frame_with(events, gizmo(), |ui| {
+                let selection = ui.interact(viewport, Id::new("selection"), Sense::drag());
+                selection_dragged |= selection.dragged();
+            });
+            transformed |= interaction.result.is_some();
+        }
+        (transformed, selection_dragged)
+    };
+
+    // The pointer moves away from the handle on the frame it is pressed
+    assert_eq!(drag(empty, empty + Vec2::new(40.0, 20.0)), (false, true));
+    assert_eq!(drag(handle, handle + Vec2::new(40.0, 0.0)), (true, false));
+}
+
+#[test]
+fn non_interactable_gizmo_ends_drag() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = |interactable| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .interactable(interactable)
+    };
+
+    let hovered = harness.frame(vec![Event::PointerMoved(from)], gizmo(false));
+    assert!(hovered.hovered.is_none());
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo(true));
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo(true));
+    let dragging = harness.frame(
+        vec![Event::PointerMoved(from + Vec2::new(20.0, 0.0))],
+        gizmo(true),
+    );
+    assert!(dragging.result.is_some());
+
+    // The drag does not continue, even when the gizmo becomes interactable again
+    for interactable in [false, true] {
+        let interaction = harness.frame(
+            vec![Event::PointerMoved(from + Vec2::new(40.0, 0.0))],
+            gizmo(interactable),
+        );
+        assert!(interaction.result.is_none());
+    }
+}
+
+#[test]
+fn drag_continues_when_mode_changes() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = |mode| Gizmo::new("gizmo").mode(mode);
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo(GizmoMode::Translate));
+    let _ = harness.frame(
+        vec![pointer_button(from, true)],
+        gizmo(GizmoMode::Translate),
+    );
+    let before = harness
+        .frame(
+            vec![Event::PointerMoved(from + Vec2::new(20.0, 0.0))],
+            gizmo(GizmoMode::Translate),
+        )
+        .result
+        .expect("the drag should produce a result");
+
+    // The application switches to rotation during the drag
+    let after = harness
+        .frame(
+            vec![Event::PointerMoved(from + Vec2::new(40.0, 0.0))],
+            gizmo(GizmoMode::Rotate),
+        )
+        .result
+        .expect("the drag should continue after the mode changed");
+    assert_eq!(handle_name(&after), Some("Translate X".to_owned()));
+    let (before, after) = (
+        Vec3::from(before.translation),
+        Vec3::from(after.translation),
+    );
+    assert!(
+        after.abs_diff_eq(before * 2.0, 1e-3),
+        "translation jumped from {before} to {after}"
+    );
+
+    // Once released, the gizmo has the new mode
+    let _ = harness.frame(
+        vec![pointer_button(from + Vec2::new(40.0, 0.0), false)],
+        gizmo(GizmoMode::Rotate),
+    );
+    let hovered = harness.frame(vec![Event::PointerMoved(from)], gizmo(GizmoMode::Rotate));
+    assert!(hovered
+        .hovered
+        .is_some_and(|handle| handle.mode == GizmoMode::Rotate));
+}
+
+#[test]
+fn change_id_is_bumped_only_when_the_transform_changes() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let mut model = Mat4::IDENTITY;
+    let gizmo = |model: Mat4| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .model_matrix(model.to_cols_array_2d())
+            .snapping(true)
+            .snap_distance(1.0)
+    };
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo(model));
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo(model));
+
+    // A short drag is snapped back to the start
+    let interaction = harness.frame(
+        vec![Event::PointerMoved(from + Vec2::new(20.0, 0.0))],
+        gizmo(model),
+    );
+    let result = interaction
+        .result
+        .expect("the drag should produce a result");
+    assert!(result.is_identity_delta());
+    assert_eq!(interaction.change_id, 0);
+
+    let interaction = harness.frame(
+        vec![Event::PointerMoved(from + Vec2::new(100.0, 0.0))],
+        gizmo(model),
+    );
+    let result = interaction
+        .result
+        .expect("the drag should produce a result");
+    assert!(!result.is_identity_delta());
+    assert_eq!(interaction.change_id, 1);
+    model = Mat4::from(result.transform());
+
+    // Frames where the pointer does not move produce no result
+    let interaction = harness.frame(vec![], gizmo(model));
+    assert!(interaction.result.is_none());
+    assert_eq!(interaction.change_id, 1);
+
+    let interaction = harness.frame(
+        vec![pointer_button(from + Vec2::new(100.0, 0.0), false)],
+        gizmo(model),
+    );
+    assert_eq!(interaction.change_id, 1);
+}
+
+#[test]
+fn still_pointer_produces_no_result_until_release() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(40.0, 0.0);
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo());
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo());
+    let moved = harness
+        .frame(vec![Event::PointerMoved(to)], gizmo())
+        .result
+        .expect("the drag should produce a result");
+    assert!(!moved.drag_ended);
+
+    for _ in 0..3 {
+        let interaction = harness.frame(vec![], gizmo());
+        assert!(interaction.active);
+        assert!(interaction.result.is_none());
+    }
+
+    let interaction = harness.frame(vec![pointer_button(to, false)], gizmo());
+    assert!(!interaction.active);
+    let released = interaction
+        .result
+        .expect("the release should produce a final result");
+    assert!(released.drag_ended);
+    assert_eq!(released.translation, moved.translation);
+
+    let interaction = harness.frame(vec![], gizmo());
+    assert!(interaction.result.is_none());
+}
+
+#[test]
+fn parent_matrix_gives_local_results() {
+    // The parent is rotated so that the local X axis points up on the screen
+    let parent =
+        Mat4::from_translation(Vec3::new(0.0, 0.0, 1.0)) * Mat4::from_rotation_z(FRAC_PI_2);
+    let local = Mat4::from_translation(Vec3::new(0.0, 0.0, -1.0));
+    let gizmo = |orientation| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .orientation(orientation)
+            .parent_matrix(parent.to_cols_array_2d())
+            .model_matrix(local.to_cols_array_2d())
+    };
+
+    // The global X arrow points right and moves along the world X axis,
+    // which is the negative local Y axis of the parent
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let result = harness
+        .drag(from, from + Vec2::new(40.0, 0.0), || {
+            gizmo(GizmoOrientation::Global)
+        })
+        .expect("dragging the global X arrow should produce a result");
+    let translation = Vec3::from(result.translation);
+    assert!(translation.x.abs() < 1e-4, "{translation}");
+    assert!(translation.y < -0.1, "{translation}");
+    assert!((translation.z + 1.0).abs() < 1e-4, "{translation}");
+    assert_eq!(result.local_transform(), result.transform());
+    assert!(Mat4::from(result.start_transform).abs_diff_eq(local, 1e-5));
+
+    // The local X arrow points up and moves along the local X axis
+    let mut harness = Harness::front();
+    let from = harness.center() - Vec2::new(0.0, GIZMO_SIZE * 0.6);
+    let result = harness
+        .drag(from, from - Vec2::new(0.0, 40.0), || {
+            gizmo(GizmoOrientation::Local)
+        })
+        .expect("dragging the local X arrow should produce a result");
+    let translation = Vec3::from(result.translation);
+    assert!(translation.x > 0.1, "{translation}");
+    assert!(translation.y.abs() < 1e-4, "{translation}");
+    assert_eq!(handle_name(&result), Some("Translate X".to_owned()));
+}
+
+#[test]
+fn placement_modifier_places_object_on_surface() {
+    // The surface is a wall behind the gizmo, facing the camera
+    let wall_z = -5.0;
+    let place = move |query: PlacementQuery| {
+        let origin = Vec3::from(query.ray_origin);
+        let direction = Vec3::from(query.ray_direction);
+        let t = (wall_z - origin.z) / direction.z;
+        Some(((origin + direction * t).into(), Vec3::Z.into()))
+    };
+    let gizmo = |align_to_normal| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .placement_fn(place)
+            .align_to_normal(align_to_normal)
+    };
+
+    let mut harness = Harness::front();
+    let from = harness.center();
+    let to = from + Vec2::new(60.0, 0.0);
+
+    // Without the modifier, the view plane handle moves on the plane through the gizmo
+    let result = harness
+        .drag(from, to, || gizmo(false))
+        .expect("the drag should produce a result");
+    assert!(Vec3::from(result.translation).z.abs() < 1e-4);
+
+    harness.set_modifiers(Modifiers::ALT);
+    let result = harness
+        .drag(from, to, || gizmo(false))
+        .expect("the drag should produce a result");
+    let translation = Vec3::from(result.translation);
+    assert!((translation.z - wall_z).abs() < 1e-3, "{translation}");
+    assert!(translation.x > 0.5, "{translation}");
+    assert!(Quat::from(result.rotation).abs_diff_eq(Quat::IDENTITY, 1e-5));
+
+    let result = harness
+        .drag(from, to, || gizmo(true))
+        .expect("the drag should produce a result");
+    let up = Quat::from(result.rotation) * Vec3::Y;
+    assert!(up.abs_diff_eq(Vec3::Z, 1e-4), "{up}");
+}
+
+#[test]
+fn drag_reports_duration_and_frame_count() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(20.0, 0.0);
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    // The harness advances the time by 0.1 seconds per frame
+    let interactions = [
+        vec![Event::PointerMoved(from)],
+        vec![pointer_button(from, true)],
+        vec![Event::PointerMoved(to)],
+        vec![Event::PointerMoved(to)],
+        vec![pointer_button(to, false)],
+        vec![Event::PointerMoved(from)],
+    ]
+    .map(|events| {
+        let interaction = harness.frame(events, gizmo());
+        (interaction.drag_duration, interaction.frame_count)
+    });
+
+    let expected = [(0.0, 0), (0.0, 1), (0.1, 2), (0.2, 3), (0.3, 3), (0.0, 0)];
+    for ((duration, frame_count), (expected_duration, expected_frame_count)) in
+        interactions.into_iter().zip(expected)
+    {
+        assert!((duration - expected_duration).abs() < 1e-5);
+        assert_eq!(frame_count, expected_frame_count);
+    }
+}
+
+#[test]
+fn scrolling_during_drag_adjusts_proportional_radius() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(20.0, 0.0);
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .proportional_radius(Some(2.0))
+    };
+
+    let mut results = Vec::new();
+    for events in [
+        vec![Event::PointerMoved(from)],
+        vec![pointer_button(from, true)],
+        vec![Event::PointerMoved(to)],
+        vec![Event::Scroll(Vec2::new(0.0, 50.0))],
+        vec![pointer_button(to, false)],
+    ] {
+        results.extend(harness.frame(events, gizmo()).result);
+    }
+
+    // The final result of the drag keeps the adjusted radius
+    let radii = results
+        .iter()
+        .map(|result| (result.proportional_radius, result.drag_ended))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        radii,
+        [(Some(2.0), false), (Some(2.5), false), (Some(2.5), true)]
+    );
+}
+
+#[test]
+fn pick_tolerance_follows_egui_style() {
+    let mut harness = Harness::front();
+    let pos = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 12.0);
+    let hovered = |harness: &mut Harness, gizmo: Gizmo| {
+        harness
+            .frame(
+                vec![Event::PointerMoved(pos)],
+                gizmo.mode(GizmoMode::Translate),
+            )
+            .hovered
+            .map(|handle| handle.to_string())
+    };
+
+    assert_eq!(hovered(&mut harness, Gizmo::new("gizmo")), None);
+
+    let mut style = egui::Style::default();
+    style.interaction.resize_grab_radius_side = 15.0;
+    harness.set_style(style);
+    assert_eq!(
+        hovered(&mut harness, Gizmo::new("gizmo")),
+        Some("Translate X".to_owned())
+    );
+
+    // An explicit tolerance overrides the style
+    assert_eq!(
+        hovered(&mut harness, Gizmo::new("gizmo").pick_tolerance(1.0)),
+        None
+    );
+}
+
+#[test]
+fn debug_info_reports_resolved_configuration() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+
+    let info = harness
+        .frame(vec![], Gizmo::new("gizmo"))
+        .debug_info
+        .expect("the configuration is valid");
+    assert!(!info.left_handed);
+    assert!(!info.orthographic);
+    assert!(!info.culled);
+    assert!(info.scale_factor > 0.0);
+    assert!(info
+        .gizmo_screen_pos
+        .is_some_and(|pos| pos.distance(center) < 1e-3));
+
+    // Behind the camera
+    let model = Mat4::from_translation(Vec3::new(0.0, 0.0, 20.0));
+    let info = harness
+        .frame(
+            vec![],
+            Gizmo::new("gizmo").model_matrix(model.to_cols_array_2d()),
+        )
+        .debug_info
+        .expect("the configuration is valid");
+    assert!(info.culled);
+    assert!(info.gizmo_screen_pos.is_none());
+}
+
+#[test]
+fn other_buttons_do_not_affect_drag() {
+    let from = Harness::front().center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    let button = |x, button, pressed| Event::PointerButton {
+        pos: from + Vec2::new(x, 0.0),
+        button,
+        pressed,
+        modifiers: Default::default(),
+    };
+    let moved = |x| Event::PointerMoved(from + Vec2::new(x, 0.0));
+
+    let drag = |other_buttons: bool| {
+        let mut harness = Harness::front();
+        let mut frames = vec![
+            vec![Event::PointerMoved(from)],
+            vec![pointer_button(from, true)],
+            vec![moved(10.0)],
+            vec![moved(20.0)],
+            vec![moved(30.0)],
+            vec![moved(40.0)],
+        ];
+        if other_buttons {
+            frames[3].push(button(20.0, PointerButton::Secondary, true));
+            frames[4].push(button(30.0, PointerButton::Middle, true));
+            frames[4].push(button(30.0, PointerButton::Secondary, false));
+            frames[5].push(button(40.0, PointerButton::Middle, false));
+        }
+        frames
+            .into_iter()
+            .map(|events| {
+                harness
+                    .frame(events, gizmo())
+                    .result
+                    .map(|result| Vec3::from(result.translation))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let expected = drag(false);
+    assert!(expected.last().is_some_and(Option::is_some));
+    assert_eq!(drag(true), expected);
+}
+
+#[test]
+fn planar_translation_shows_large_plane_and_normal_axis() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .planar_translation(GizmoPlane::XY, 1.0)
+            .snapping(true)
+            .snap_distance(0.5)
+    };
+    let hovered = |harness: &mut Harness, offset: Vec2| {
+        harness
+            .frame(vec![Event::PointerMoved(center + offset)], gizmo())
+            .hovered
+            .map(|handle| handle.to_string())
+    };
+
+    // The plane is pickable across its whole area, and there is no X axis
+    assert_eq!(
+        hovered(&mut harness, Vec2::splat(GIZMO_SIZE * 0.4)),
+        Some("Translate XY plane".to_owned())
+    );
+    assert_eq!(
+        hovered(&mut harness, Vec2::new(GIZMO_SIZE * 0.8, 0.0)),
+        None
+    );
+
+    let from = center + Vec2::new(GIZMO_SIZE * 0.3, -GIZMO_SIZE * 0.3);
+    let result = harness
+        .drag(from, from + Vec2::new(80.0, 50.0), gizmo)
+        .expect("dragging the plane should produce a result");
+    let translation = Vec3::from(result.translation);
+    assert_eq!(translation.z, 0.0);
+    assert!(translation.x > 0.0 && translation.y < 0.0);
+    for component in [translation.x, translation.y] {
+        assert!((component / 0.5 - (component / 0.5).round()).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn view_override_changes_view_plane() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(2.0, -2.0);
+    let to = from + Vec2::new(0.0, -30.0);
+
+    // Without the override, the view plane faces the camera
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+    let result = harness
+        .drag(from, to, gizmo)
+        .expect("dragging the view plane should produce a result");
+    assert_eq!(
+        handle_name(&result),
+        Some("Translate view plane".to_owned())
+    );
+    let translation = Vec3::from(result.translation);
+    assert!(translation.y > 0.0 && translation.z.abs() < 1e-4);
+
+    // The viewer looks down at the gizmo from above at 45 degrees. The Z arrow
+    // is then no longer hidden, and it overlaps the view plane on the screen.
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .view_override(Vec3::new(0.0, -1.0, -1.0), Vec3::new(0.0, 1.0, -1.0))
+            .pick_priority(|handle| i32::from(handle.direction == GizmoDirection::View))
+    };
+    let result = harness
+        .drag(from, to, gizmo)
+        .expect("dragging the view plane should produce a result");
+    assert_eq!(
+        handle_name(&result),
+        Some("Translate view plane".to_owned())
+    );
+    let translation = Vec3::from(result.translation);
+    assert!(translation.y > 0.0);
+    assert!(
+        (translation.y + translation.z).abs() < 1e-3,
+        "translation {translation} is not in the overridden view plane"
+    );
+}
+
+#[test]
+fn translate_along_x_axis() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+
+    let world_units_per_pixel = harness
+        .frame(vec![Event::PointerMoved(center)], gizmo())
+        .world_units_per_pixel;
+
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let result = harness
+        .drag(from, from + Vec2::new(50.0, 20.0), gizmo)
+        .expect("dragging the X arrow should produce a result");
+
+    assert_eq!(handle_name(&result), Some("Translate X".to_owned()));
+    assert!(!result.snapped);
+    assert_eq!(result.snap_increment, None);
+
+    // The X axis is parallel to the screen, so the pointer movement
+    // along it is mapped directly to world units.
+    let translation = Vec3::from(result.translation);
+    let expected = Vec3::X * 50.0 * world_units_per_pixel;
+    assert!(
+        translation.abs_diff_eq(expected, 1e-3),
+        "translation {translation} is not {expected}"
+    );
+}
+
+#[test]
+fn rotate_around_z_axis() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let ring_point = |angle: f32| center + Vec2::angled(angle.to_radians()) * GIZMO_SIZE;
+
+    // The Z ring faces the camera and is at the edge of the arcball
+    let result = harness
+        .drag(ring_point(45.0), ring_point(135.0), || {
+            Gizmo::new("gizmo")
+                .mode(GizmoMode::Rotate)
+                .pick_priority(|handle| i32::from(handle.transform_kind.is_some()))
+        })
+        .expect("dragging the Z ring should produce a result");
+
+    assert_eq!(handle_name(&result), Some("Rotate Z".to_owned()));
+
+    // Screen space y grows downwards, so the drag is clockwise
+    // as seen from the camera, which is a negative rotation around Z.
+    let rotation = Quat::from(result.rotation);
+    let expected = Quat::from_rotation_z(-FRAC_PI_2);
+    assert!(
+        rotation.abs_diff_eq(expected, 1e-3),
+        "rotation {rotation} is not {expected}"
+    );
+}
+
+#[test]
+fn snapping_can_be_disabled_per_mode() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let gizmo = |mode| {
+        Gizmo::new("gizmo")
+            .mode(mode)
+            .snapping(true)
+            .snapping_translate(false)
+            .snap_angle(5_f32.to_radians())
+            .pick_priority(|handle| i32::from(handle.transform_kind.is_some()))
+    };
+
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let result = harness
+        .drag(from, from + Vec2::new(37.0, 0.0), || {
+            gizmo(GizmoMode::Translate)
+        })
+        .expect("dragging the X arrow should produce a result");
+    assert_eq!(handle_name(&result), Some("Translate X".to_owned()));
+    assert!(!result.snapped);
+    assert_eq!(result.value, result.raw_value);
+
+    let ring_point = |angle: f32| center + Vec2::angled(angle.to_radians()) * GIZMO_SIZE;
+    let result = harness
+        .drag(ring_point(45.0), ring_point(78.0), || {
+            gizmo(GizmoMode::Rotate)
+        })
+        .expect("dragging the Z ring should produce a result");
+    assert_eq!(handle_name(&result), Some("Rotate Z".to_owned()));
+    assert!(result.snapped);
+    let angle = Vec3::from(result.value.unwrap()).length().to_degrees();
+    assert!(
+        (angle / 5.0 - (angle / 5.0).round()).abs() < 1e-3,
+        "{angle} degrees"
+    );
+}
+
+#[test]
+fn scale_along_x_axis() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+
+    // Scaling is relative to the distance of the pointer from the gizmo origin
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = center + Vec2::new(GIZMO_SIZE * 1.2, 0.0);
+    let result = harness
+        .drag(from, to, || Gizmo::new("gizmo").mode(GizmoMode::Scale))
+        .expect("dragging the X scale handle should produce a result");
+
+    assert_eq!(handle_name(&result), Some("Scale X".to_owned()));
+
+    let scale = Vec3::from(result.scale);
+    let expected = Vec3::new(2.0, 1.0, 1.0);
+    assert!(
+        scale.abs_diff_eq(expected, 1e-3),
+        "scale {scale} is not {expected}"
+    );
+}
+
+#[test]
+fn scale_with_anchored_side_follows_the_pointer() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let gizmo = |model: Mat4| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Scale)
+            .model_matrix(model.to_cols_array_2d())
+            .local_bounds(Vec3::NEG_ONE.into(), Vec3::ONE.into())
+            .scale_origin(ScaleOrigin::NegativeSide)
+    };
+
+    // The host applies the result on every frame, which moves the gizmo origin
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = center + Vec2::new(GIZMO_SIZE * 1.2, 0.0);
+    let mut model = Mat4::IDENTITY;
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo(model));
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo(model));
+    for step in 1..=4 {
+        let pos = from.lerp(to, step as f32 / 4.0);
+        if let Some(result) = harness
+            .frame(vec![Event::PointerMoved(pos)], gizmo(model))
+            .result
+        {
+            model = Mat4::from(result.transform());
+        }
+    }
+    let _ = harness.frame(vec![pointer_button(to, false)], gizmo(model));
+
+    let (scale, _, translation) = model.to_scale_rotation_translation();
+    assert!(
+        (scale.x - 2.0).abs() < 1e-3,
+        "scale {scale} does not follow the pointer"
+    );
+    let anchored_face = translation.x - scale.x;
+    assert!(
+        (anchored_face + 1.0).abs() < 1e-3,
+        "the -X face moved to {anchored_face}"
+    );
+}
+
+#[test]
+fn snapped_translation_keeps_raw_value() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .snapping(true)
+            .snap_distance(0.5)
+    };
+
+    let world_units_per_pixel = harness
+        .frame(vec![Event::PointerMoved(center)], gizmo())
+        .world_units_per_pixel;
+
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let result = harness
+        .drag(from, from + Vec2::new(50.0, 0.0), gizmo)
+        .expect("dragging the X arrow should produce a result");
+
+    let raw_value = Vec3::from(result.raw_value.unwrap());
+    let expected_raw_value = Vec3::X * 50.0 * world_units_per_pixel;
+    assert!(
+        raw_value.abs_diff_eq(expected_raw_value, 1e-3),
+        "raw value {raw_value} is not {expected_raw_value}"
+    );
+
+    let value = Vec3::from(result.value.unwrap());
+    let expected_value = Vec3::X * (expected_raw_value.x / 0.5).round() * 0.5;
+    assert!(
+        value.abs_diff_eq(expected_value, 1e-5),
+        "value {value} is not {expected_value}"
+    );
+    assert_eq!(result.translation, value.into());
+    assert!(result.snapped);
+    assert_eq!(result.snap_increment, Some(0.5));
+}
+
+#[test]
+fn preview_only_drag_is_shown_at_the_dragged_pose() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(40.0, 0.0);
+    // The host never applies the results, so the model matrix stays at the origin
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .preview_only(true)
+    };
+
+    let start_rect = harness
+        .frame(vec![Event::PointerMoved(from)], gizmo())
+        .bounding_rect;
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo());
+    let moved = harness
+        .frame(vec![Event::PointerMoved(to)], gizmo())
+        .result
+        .expect("the drag should produce a result");
+    assert!(moved.translation.x > 0.0);
+
+    // The result is repeated while the pointer stays still, and the gizmo follows the drag
+    let interaction = harness.frame(vec![], gizmo());
+    let still = interaction
+        .result
+        .expect("a previewed drag should produce a result on every frame");
+    assert!(still.approx_eq(&moved, 1e-5));
+    assert!(interaction.bounding_rect.center().x > start_rect.center().x + 20.0);
+
+    let released = harness
+        .frame(vec![pointer_button(to, false)], gizmo())
+        .result
+        .expect("the release should produce a final result");
+    assert!(released.drag_ended);
+    assert!(released.approx_eq(&moved, 1e-5));
+
+    // Without a commit, the gizmo returns to the model matrix
+    let interaction = harness.frame(vec![], gizmo());
+    assert!(interaction.result.is_none());
+    assert!((interaction.bounding_rect.center().x - start_rect.center().x).abs() < 1.0);
+}
+
+#[test]
+fn smoothing_follows_the_pointer_and_releases_at_the_exact_value() {
+    let from = Harness::front().center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(40.0, 0.0);
+
+    let exact = Harness::front()
+        .drag(from, to, || Gizmo::new("gizmo").mode(GizmoMode::Translate))
+        .expect("the drag should produce a result");
+
+    let mut harness = Harness::front();
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .smoothing(0.1)
+    };
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo());
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo());
+    let moved = harness
+        .frame(vec![Event::PointerMoved(to)], gizmo())
+        .result
+        .expect("the drag should produce a result");
+    assert!(moved.translation.x > 0.0);
+    assert!(moved.translation.x < exact.translation.x * 0.9);
+
+    // The smoothed value keeps approaching the pointer while it stays still
+    let still = harness
+        .frame(vec![], gizmo())
+        .result
+        .expect("the smoothed drag should produce a result");
+    assert!(still.translation.x > moved.translation.x);
+    assert!(still.translation.x < exact.translation.x);
+
+    let released = harness
+        .frame(vec![pointer_button(to, false)], gizmo())
+        .result
+        .expect("the release should produce a final result");
+    assert!(released.drag_ended);
+    assert!(released.approx_eq(&exact, 1e-5));
+}
+
+#[test]
+fn hidden_frame_keeps_the_change_id_and_the_drag() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = |model: Mat4| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .model_matrix(model.to_cols_array_2d())
+    };
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo(Mat4::IDENTITY));
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo(Mat4::IDENTITY));
+    let interaction = harness.frame(
+        vec![Event::PointerMoved(from + Vec2::new(40.0, 0.0))],
+        gizmo(Mat4::IDENTITY),
+    );
+    let change_id = interaction.change_id;
+    assert!(change_id > 0);
+
+    // The origin of the gizmo is behind the camera for one frame
+    let behind = Mat4::from_translation(Vec3::new(0.0, 0.0, 20.0));
+    let interaction = harness.frame(vec![], gizmo(behind));
+    assert_eq!(interaction.change_id, change_id);
+
+    let interaction = harness.frame(vec![], gizmo(Mat4::IDENTITY));
+    assert!(interaction.change_id >= change_id);
+    assert!(interaction.active);
+}
+
+#[test]
+fn drag_is_precise_far_from_the_world_origin() {
+    let position = Vec3::new(1e6, 0.0, 1e6);
+    let mut harness = Harness::new(Mat4::look_at_rh(
+        position + Vec3::new(0.0, 0.0, 10.0),
+        position,
+        Vec3::Y,
+    ));
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let gizmo = || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .model_matrix(Mat4::from_translation(position).to_cols_array_2d())
+    };
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo());
+    let interaction = harness.frame(vec![pointer_button(from, true)], gizmo());
+    let units_per_pixel = interaction.world_units_per_pixel as f64;
+
+    // Each pixel of pointer movement moves the object by the size of a pixel,
+    // which is about a centimeter at this distance
+    for pixels in 3..=6 {
+        let pos = from + Vec2::new(pixels as f32, 0.0);
+        let result = harness
+            .frame(vec![Event::PointerMoved(pos)], gizmo())
+            .result
+            .expect("the drag should produce a result");
+        let delta = result.translation_f64().x - position.x as f64;
+        let expected = pixels as f64 * units_per_pixel;
+        assert!(
+            (delta - expected).abs() < 1e-4,
+            "moved by {delta} instead of {expected} after {pixels} pixels"
+        );
+    }
+}
+
+#[test]
+fn gizmo_recovers_after_a_non_finite_model_matrix() {
+    let mut harness = Harness::front();
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(40.0, 0.0);
+    let gizmo = |model: Mat4| {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .model_matrix(model.to_cols_array_2d())
+    };
+
+    let _ = harness.frame(vec![Event::PointerMoved(from)], gizmo(Mat4::IDENTITY));
+    let _ = harness.frame(vec![pointer_button(from, true)], gizmo(Mat4::IDENTITY));
+    let nan = Mat4::from_translation(Vec3::new(f32::NAN, 0.0, 0.0));
+    let interaction = harness.frame(vec![Event::PointerMoved(to)], gizmo(nan));
+    assert!(interaction.result.is_none());
+    let _ = harness.frame(vec![pointer_button(to, false)], gizmo(Mat4::IDENTITY));
+
+    let interaction = harness.frame(vec![Event::PointerMoved(from)], gizmo(Mat4::IDENTITY));
+    assert_eq!(
+        interaction.hovered.map(|handle| handle.direction),
+        Some(GizmoDirection::X)
+    );
+
+    let result = harness
+        .drag(from, to, || gizmo(Mat4::IDENTITY))
+        .expect("the drag should produce a result after the matrix recovered");
+    let translation = Vec3::from(result.translation);
+    assert!(translation.is_finite());
+    assert!(translation.x > 0.0);
+}
+
+#[test]
+fn scale_snap_modes_snap_in_log_space() {
+    let center = Harness::front().center();
+    // The unsnapped scale factor of the drag is 2
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = center + Vec2::new(GIZMO_SIZE * 1.2, 0.0);
+    let scale_x = |mode: ScaleSnapMode| {
+        let result = Harness::front()
+            .drag(from, to, || {
+                Gizmo::new("gizmo")
+                    .mode(GizmoMode::Scale)
+                    .snapping(true)
+                    .scale_snap_mode(mode)
+            })
+            .expect("dragging the X scale handle should produce a result");
+        result.scale.x
+    };
+
+    // Steps of 10% are 1.1^7 and 1.1^8 around 2
+    assert!((scale_x(ScaleSnapMode::Percent(10.0)) - 1.1_f32.powi(7)).abs() < 1e-3);
+    assert!((scale_x(ScaleSnapMode::PowersOf(2.0)) - 2.0).abs() < 1e-3);
+    assert!((scale_x(ScaleSnapMode::PowersOf(0.5)) - 2.0).abs() < 1e-3);
+
+    // Invalid bases disable the snapping instead of giving non-finite scales
+    for mode in [
+        ScaleSnapMode::PowersOf(1.0),
+        ScaleSnapMode::PowersOf(0.0),
+        ScaleSnapMode::PowersOf(-2.0),
+        ScaleSnapMode::Percent(0.0),
+        ScaleSnapMode::Percent(-150.0),
+    ] {
+        let scale = scale_x(mode);
+        assert!(
+            (scale - 2.0).abs() < 1e-2,
+            "{mode:?} gave the scale {scale}"
+        );
+    }
+}
+
+#[test]
+fn touch_gesture_reports_the_current_center_of_the_touches() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let offset = Vec2::new(20.0, 10.0);
+    let from = [center - Vec2::X * 10.0, center + Vec2::X * 10.0];
+    let to = [
+        from[0] + offset - Vec2::X * 5.0,
+        from[1] + offset + Vec2::X * 5.0,
+    ];
+
+    let results = harness.touch(from, to, || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Scale)
+            .touch_gestures(true)
+    });
+
+    let result = results.last().expect("the gesture should produce a result");
+    assert!(
+        (result.current_screen - (center + offset)).length() < 1e-3,
+        "the gesture is at {:?}",
+        result.current_screen
+    );
+    assert!((result.drag_start_screen - center).length() < 1e-3);
+}
+
+#[test]
+fn pinch_scales_uniformly() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let from = [center - Vec2::X * 10.0, center + Vec2::X * 10.0];
+    let to = [center - Vec2::X * 22.0, center + Vec2::X * 22.0];
+
+    let results = harness.touch(from, to, || {
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Scale)
+            .touch_gestures(true)
+            .snapping(true)
+            .snap_scale(0.5)
+    });
+
+    // The pinch of 2.2 is snapped to 2.0
+    let result = results.last().expect("the pinch should produce a result");
+    assert_eq!(result.mode, GizmoMode::Scale);
+    assert!((Vec3::from(result.scale) - Vec3::splat(2.0)).length() < 1e-4);
+    assert!(result.snapped);
+    assert_eq!(result.snap_increment, Some(0.5));
+}
+
+#[test]
+fn twist_rotates_around_the_view_axis() {
+    let eye = Vec3::new(0.0, 0.0, 10.0);
+    let aspect_ratio = SCREEN_SIZE.x / SCREEN_SIZE.y;
+    let angle = 30_f32.to_radians();
+
+    // Twisting clockwise on the screen rotates clockwise on the screen. The cameras
+    // look along -Z, so that is around -Z with a right-handed camera, and around +Z
+    // with a left-handed one, which mirrors the X axis on the screen.
+    for (mut harness, axis) in [
+        (Harness::front(), Vec3::NEG_Z),
+        (
+            Harness::with_projection(
+                Mat4::look_at_lh(eye, Vec3::ZERO, Vec3::Y),
+                Mat4::perspective_lh(45_f32.to_radians(), aspect_ratio, 0.1, 100.0),
+            ),
+            Vec3::Z,
+        ),
+    ] {
+        let center = harness.center();
+        let from = [center - Vec2::X * 20.0, center + Vec2::X * 20.0];
+        let arm = Vec2::angled(angle) * 20.0;
+        let to = [center - arm, center + arm];
+
+        let results = harness.touch(from, to, || {
+            Gizmo::new("gizmo")
+                .mode(GizmoMode::Rotate)
+                .touch_gestures(true)
+                .snapping(true)
+                .snap_angle(15_f32.to_radians())
+        });
+
+        let result = results.last().expect("the twist should produce a result");
+        let expected = Quat::from_axis_angle(axis, angle);
+        assert_eq!(result.mode, GizmoMode::Rotate);
+        assert!(
+            Quat::from(result.rotation).angle_between(expected) < 1e-3,
+            "the rotation is {:?}",
+            result.rotation
+        );
+        assert!(result.snapped);
+        assert_eq!(result.snap_increment, Some(15_f32.to_radians()));
+    }
+}
+
+#[test]
+fn touch_gestures_are_ignored_where_they_do_not_apply() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let pinch = |center: Pos2| {
+        (
+            [center - Vec2::X * 10.0, center + Vec2::X * 10.0],
+            [center - Vec2::X * 20.0, center + Vec2::X * 20.0],
+        )
+    };
+    let gizmo = |mode: GizmoMode| Gizmo::new("gizmo").mode(mode).touch_gestures(true);
+
+    // Outside of the gizmo
+    let (from, to) = pinch(center + Vec2::splat(GIZMO_SIZE * 2.0));
+    assert!(harness
+        .touch(from, to, || gizmo(GizmoMode::Scale))
+        .is_empty());
+
+    // Without scaling or rotation to apply the gesture to
+    let (from, to) = pinch(center);
+    for mode in [GizmoMode::Translate, GizmoMode::Arcball] {
+        assert!(harness.touch(from, to, || gizmo(mode)).is_empty());
+    }
+
+    // Fingers held still
+    assert!(harness
+        .touch(from, from, || gizmo(GizmoMode::Scale))
+        .is_empty());
+}
+
+#[test]
+fn visuals_override_color_takes_precedence_over_plane_colors() {
+    let mut harness = Harness::front();
+    let visuals = GizmoVisuals::default()
+        .with_x_color(Color32::GRAY)
+        .with_y_color(Color32::GRAY)
+        .with_z_color(Color32::GRAY)
+        .with_plane_colors([Color32::BLUE, Color32::GREEN, Color32::YELLOW]);
+    let color_override = GizmoVisualsOverride {
+        color: Some(Color32::RED),
+        ..Default::default()
+    };
+
+    // The XY plane faces the camera, and is the only red shape
+    let _ = harness.frame(
+        vec![],
+        Gizmo::new("gizmo")
+            .mode(GizmoMode::Translate)
+            .visuals(visuals)
+            .visuals_for(GizmoDirection::Z, TransformKind::Plane, color_override),
+    );
+    let is_red = |color: Color32| color.r() > 0 && color.g() == 0 && color.b() == 0;
+    assert!(harness.shapes().any(|shape| match shape {
+        Shape::Mesh(mesh) => mesh.vertices.iter().any(|vertex| is_red(vertex.color)),
+        _ => false,
+    }));
+}
@@ -0,0 +1,80 @@
+use egui::{Event, Key, Modifiers, TextEdit, Vec2};
+use egui_gizmo::{Gizmo, GizmoMode};
+use glam::Vec3;
+
+use common::{pointer_button, Harness, GIZMO_SIZE};
+
+mod common;
+
+fn key(key: Key) -> Vec<Event> {
+    [true, false]
+        .map(|pressed| Event::Key {
+            key,
+            physical_key: None,
+            pressed,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        })
+        .to_vec()
+}
+
+#[test]
+fn focused_text_edit_keeps_typed_keys() {
+    let mut harness = Harness::front();
+    let mut text = String::new();
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+    // The pointer hovers a handle of the gizmo while typing
+    let hover = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+
+    let _ = harness.frame_with(vec![Event::PointerMoved(hover)], gizmo(), |ui| {
+        ui.add(TextEdit::singleline(&mut text)).request_focus();
+    });
+
+    for events in [
+        vec![Event::Text("x".to_owned())],
+        key(Key::ArrowLeft),
+        vec![Event::Text("y".to_owned())],
+        key(Key::ArrowRight),
+        key(Key::Tab),
+        key(Key::Enter),
+        key(Key::Escape),
+    ] {
+        let interaction = harness.frame_with(events, gizmo(), |ui| {
+            ui.add(TextEdit::singleline(&mut text));
+        });
+        assert!(!interaction.active);
+        assert!(interaction.result.is_none());
+    }
+
+    assert_eq!(text, "yx");
+}
+
+#[test]
+fn pointer_drag_works_while_text_edit_has_focus() {
+    let mut harness = Harness::front();
+    let mut text = String::new();
+    let gizmo = || Gizmo::new("gizmo").mode(GizmoMode::Translate);
+    let from = harness.center() + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let to = from + Vec2::new(40.0, 0.0);
+
+    let mut result = None;
+    for (i, events) in [
+        vec![Event::PointerMoved(from)],
+        vec![pointer_button(from, true)],
+        vec![Event::PointerMoved(to)],
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let interaction = harness.frame_with(events, gizmo(), |ui| {
+            let response = ui.add(TextEdit::singleline(&mut text));
+            if i == 0 {
+                response.request_focus();
+            }
+        });
+        result = interaction.result.or(result);
+    }
+
+    let result = result.expect("the drag should produce a result");
+    assert!(Vec3::from(result.translation).x > 0.1);
+}
@@ -0,0 +1,234 @@
+use egui::{Event, Pos2, Rect, Vec2};
+use egui_gizmo::{is_trs, Gizmo, GizmoError, GizmoMode, GizmoOrientation};
+use glam::{Mat4, Quat, Vec3, Vec4};
+use mint::{ColumnMatrix4, RowMatrix4};
+
+use common::{Harness, GIZMO_SIZE};
+
+mod common;
+
+/// Matrix with the X axis sheared along Y
+fn sheared(matrix: Mat4) -> Mat4 {
+    let mut shear = Mat4::IDENTITY;
+    shear.y_axis.x = 0.5;
+    matrix * shear
+}
+
+/// Matrix with a perspective term in its bottom row
+fn with_perspective(matrix: Mat4) -> Mat4 {
+    let mut matrix = matrix;
+    matrix.x_axis.w = 0.1;
+    matrix
+}
+
+#[test]
+fn is_trs_accepts_translation_rotation_and_scale() {
+    let matrix = Mat4::from_scale_rotation_translation(
+        Vec3::new(2.0, -1.0, 0.5),
+        Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.2, 2.0),
+        Vec3::new(1.0, 2.0, 3.0),
+    );
+    assert!(is_trs(matrix.to_cols_array_2d(), 1e-4));
+}
+
+#[test]
+fn is_trs_rejects_shear_and_perspective() {
+    let matrix = Mat4::from_rotation_z(0.5);
+    assert!(!is_trs(sheared(matrix).to_cols_array_2d(), 1e-4));
+    assert!(!is_trs(with_perspective(matrix).to_cols_array_2d(), 1e-4));
+
+    let mut homogeneous = matrix;
+    homogeneous.w_axis = Vec4::new(0.0, 0.0, 0.0, 2.0);
+    assert!(!is_trs(homogeneous.to_cols_array_2d(), 1e-4));
+}
+
+#[test]
+fn try_interact_rejects_non_trs_model_matrix() {
+    let mut harness = Harness::front();
+    for model in [sheared(Mat4::IDENTITY), with_perspective(Mat4::IDENTITY)] {
+        let error = harness
+            .try_frame(
+                vec![],
+                Gizmo::new("gizmo").model_matrix(model.to_cols_array_2d()),
+            )
+            .expect_err("the model matrix should be rejected");
+        assert_eq!(error, GizmoError::NonTrsModelMatrix);
+    }
+}
+
+#[test]
+fn try_interact_rejects_invalid_camera() {
+    let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+    for (projection, expected) in [
+        (Mat4::IDENTITY, GizmoError::MissingProjection),
+        (Mat4::ZERO, GizmoError::NonInvertibleViewProjection),
+    ] {
+        let mut harness = Harness::with_projection(view, projection);
+        let error = harness
+            .try_frame(vec![], Gizmo::new("gizmo"))
+            .expect_err("the camera should be rejected");
+        assert_eq!(error, expected);
+    }
+}
+
+#[test]
+fn degenerate_viewport_skips_the_gizmo() {
+    let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+    let viewport = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 0.0));
+    let mut harness = Harness::with_viewport(view, viewport);
+
+    let error = harness
+        .try_frame(vec![], Gizmo::new("gizmo"))
+        .expect_err("the viewport should be rejected");
+    assert_eq!(error, GizmoError::DegenerateViewport);
+
+    // A collapsed panel is a normal state of the ui, and does not assert
+    let interaction = harness.frame(vec![], Gizmo::new("gizmo"));
+    assert!(interaction.result.is_none());
+    assert!(interaction.hovered.is_none());
+}
+
+#[test]
+fn non_trs_model_matrix_falls_back_to_translation_and_rotation() {
+    let translation = Vec3::new(1.0, 0.5, 0.0);
+    let rotation = Quat::from_rotation_z(0.3);
+    let trs = Mat4::from_rotation_translation(rotation, translation);
+
+    for model in [sheared(trs), with_perspective(trs)] {
+        let mut harness = Harness::front();
+        let gizmo = || {
+            Gizmo::new("gizmo")
+                .mode(GizmoMode::Translate)
+                .orientation(GizmoOrientation::Local)
+                .model_matrix(model.to_cols_array_2d())
+        };
+        let expected = harness
+            .frame(
+                vec![],
+                Gizmo::new("gizmo").model_matrix(trs.to_cols_array_2d()),
+            )
+            .debug_info
+            .and_then(|info| info.gizmo_screen_pos)
+            .expect("the gizmo should be on the screen");
+
+        let interaction = harness.frame(vec![], gizmo());
+        let debug_info = interaction.debug_info.expect("the gizmo should be shown");
+        assert!(debug_info.non_trs_model_matrix);
+        let screen_pos = debug_info
+            .gizmo_screen_pos
+            .expect("the gizmo should be on the screen");
+        assert!(screen_pos.distance(expected) < 0.5, "{screen_pos:?}");
+
+        // The X arrow follows the orthonormalized rotation
+        let x_axis = rotation * Vec3::X;
+        let pos = screen_pos + Vec2::new(x_axis.x, -x_axis.y) * GIZMO_SIZE * 0.6;
+        let interaction = harness.frame(vec![Event::PointerMoved(pos)], gizmo());
+        assert!(interaction
+            .hovered
+            .is_some_and(|handle| handle.to_string() == "Translate X"));
+    }
+}
+
+/// Model matrix whose translation ends up in the bottom row when transposed
+fn layout_model() -> Mat4 {
+    Mat4::from_translation(Vec3::new(1.0, 0.5, 0.0)) * Mat4::from_rotation_z(0.4)
+}
+
+/// Screen position and bounds of the gizmo built with its own camera matrices
+fn shown(gizmo: Gizmo) -> (Option<Pos2>, Rect) {
+    let interaction =
+        Harness::front().frame_with_own_camera(vec![], gizmo.mode(GizmoMode::Translate));
+    let debug_info = interaction
+        .debug_info
+        .expect("the debug info should be reported");
+    (debug_info.gizmo_screen_pos, interaction.bounding_rect)
+}
+
+fn camera_gizmo() -> Gizmo {
+    let (view, projection) = Harness::front().camera();
+    Gizmo::new("gizmo")
+        .view_matrix(ColumnMatrix4::from(view))
+        .projection_matrix(ColumnMatrix4::from(projection))
+}
+
+#[test]
+fn model_matrix_arrays_are_column_major() {
+    let model = layout_model();
+    let expected = shown(camera_gizmo().model_matrix(ColumnMatrix4::from(model)));
+    assert!(expected.0.is_some());
+
+    assert_eq!(
+        shown(camera_gizmo().model_matrix(model.to_cols_array_2d())),
+        expected
+    );
+    assert_eq!(
+        shown(camera_gizmo().model_matrix(model.to_cols_array())),
+        expected
+    );
+
+    // The rows of a row major array are the columns of the matrix
+    let rows = model.transpose();
+    assert_eq!(
+        shown(camera_gizmo().model_matrix_row_major(rows.to_cols_array_2d())),
+        expected
+    );
+    assert_eq!(
+        shown(camera_gizmo().model_matrix_row_major(rows.to_cols_array())),
+        expected
+    );
+    assert_eq!(
+        shown(camera_gizmo().model_matrix_row_major(RowMatrix4::from(model))),
+        expected
+    );
+
+    assert_ne!(
+        shown(camera_gizmo().model_matrix_row_major(model.to_cols_array_2d())),
+        expected
+    );
+}
+
+#[test]
+fn camera_matrix_arrays_are_column_major() {
+    let (view, projection) = Harness::front().camera();
+    let model = layout_model();
+    let gizmo = || Gizmo::new("gizmo").model_matrix(model.to_cols_array_2d());
+    let expected = shown(
+        gizmo()
+            .view_matrix(ColumnMatrix4::from(view))
+            .projection_matrix(ColumnMatrix4::from(projection)),
+    );
+    assert!(expected.0.is_some());
+
+    assert_eq!(
+        shown(
+            gizmo()
+                .view_matrix(view.to_cols_array_2d())
+                .projection_matrix(projection.to_cols_array_2d())
+        ),
+        expected
+    );
+    assert_eq!(
+        shown(
+            gizmo()
+                .view_matrix(view.to_cols_array())
+                .projection_matrix(projection.to_cols_array())
+        ),
+        expected
+    );
+    assert_eq!(
+        shown(
+            gizmo()
+                .view_matrix_row_major(view.transpose().to_cols_array_2d())
+                .projection_matrix_row_major(projection.transpose().to_cols_array_2d())
+        ),
+        expected
+    );
+    assert_eq!(
+        shown(
+            gizmo()
+                .view_matrix_row_major(view.transpose().to_cols_array())
+                .projection_matrix_row_major(projection.transpose().to_cols_array())
+        ),
+        expected
+    );
+}
@@ -0,0 +1,31 @@
+use egui::Pos2;
+use egui_gizmo::{pick_polygon, pick_polyline};
+
+#[test]
+fn polyline_is_picked_within_tolerance() {
+    let points = [
+        Pos2::new(0.0, 0.0),
+        Pos2::new(10.0, 0.0),
+        Pos2::new(10.0, 10.0),
+    ];
+
+    assert_eq!(pick_polyline(&points, Pos2::new(5.0, 2.0), 3.0), Some(2.0));
+    assert_eq!(pick_polyline(&points, Pos2::new(12.0, 5.0), 3.0), Some(2.0));
+    assert_eq!(pick_polyline(&points, Pos2::new(5.0, 5.0), 3.0), None);
+    assert_eq!(pick_polyline(&[], Pos2::ZERO, 3.0), None);
+}
+
+#[test]
+fn polygon_is_picked_inside_and_near_edges() {
+    let square = [
+        Pos2::new(0.0, 0.0),
+        Pos2::new(10.0, 0.0),
+        Pos2::new(10.0, 10.0),
+        Pos2::new(0.0, 10.0),
+    ];
+
+    assert_eq!(pick_polygon(&square, Pos2::new(5.0, 5.0), 3.0), Some(0.0));
+    // The closing edge from the last point back to the first one is included
+    assert_eq!(pick_polygon(&square, Pos2::new(-2.0, 5.0), 3.0), Some(2.0));
+    assert_eq!(pick_polygon(&square, Pos2::new(15.0, 5.0), 3.0), None);
+}
@@ -0,0 +1,155 @@
+use egui::{Pos2, Vec2};
+use egui_gizmo::{Gizmo, GizmoDirection, GizmoMode, GizmoOrientation, GizmoResult};
+use glam::{Mat4, Quat, Vec3};
+
+use common::{handle_name, Harness, GIZMO_SIZE, SCREEN_SIZE};
+
+mod common;
+
+/// Radius of the view rotation ring in pixels with the default visuals
+const VIEW_RING_RADIUS: f32 = GIZMO_SIZE + 4.0 + 5.0;
+
+/// Point on the view ring at the given screen space angle
+fn view_ring_point(angle: f32) -> Pos2 {
+    (SCREEN_SIZE / 2.0).to_pos2() + Vec2::angled(angle) * VIEW_RING_RADIUS
+}
+
+fn assert_view_ring_rotates_around(view: Mat4, forward: Vec3) {
+    let mut harness = Harness::new(view);
+    let result = harness
+        .drag(
+            view_ring_point(-60_f32.to_radians()),
+            view_ring_point(20_f32.to_radians()),
+            || {
+                Gizmo::new("gizmo")
+                    .mode(GizmoMode::Rotate)
+                    .pick_priority(|handle| i32::from(handle.direction == GizmoDirection::View))
+            },
+        )
+        .expect("dragging the view ring should produce a result");
+
+    assert_eq!(handle_name(&result), Some("Rotate view".to_owned()));
+
+    let (axis, angle) = Quat::from(result.rotation).to_axis_angle();
+    assert!(angle > 0.1, "rotation angle {angle} is too small");
+    assert!(
+        axis.cross(forward).length() < 1e-4,
+        "rotation axis {axis} is not parallel to the camera forward {forward}"
+    );
+}
+
+#[test]
+fn view_ring_rotates_around_camera_forward_with_rolled_camera() {
+    let eye = Vec3::new(3.0, 4.0, 10.0);
+    let forward = -eye.normalize();
+    let roll = Quat::from_axis_angle(forward, 35_f32.to_radians());
+    let view = Mat4::look_at_rh(eye, Vec3::ZERO, roll * Vec3::Y);
+
+    assert_view_ring_rotates_around(view, forward);
+}
+
+#[test]
+fn view_ring_rotates_around_camera_forward_with_scaled_camera() {
+    let eye = Vec3::new(3.0, 4.0, 10.0);
+    let forward = -eye.normalize();
+    let roll = Quat::from_axis_angle(forward, -50_f32.to_radians());
+    let camera = Mat4::look_at_rh(eye, Vec3::ZERO, roll * Vec3::Y).inverse()
+        * Mat4::from_scale(Vec3::new(2.0, 2.0, 0.5));
+
+    assert_view_ring_rotates_around(camera.inverse(), forward);
+}
+
+/// Object rotated 90° around the Y axis, so that its local X axis points towards -Z
+fn rotated_model() -> Mat4 {
+    Mat4::from_rotation_y(90_f32.to_radians())
+}
+
+fn rotation_gizmo(orientation: GizmoOrientation) -> Gizmo {
+    Gizmo::new("gizmo")
+        .mode(GizmoMode::Rotate)
+        .orientation(orientation)
+        .model_matrix(rotated_model().to_cols_array_2d())
+}
+
+/// Rotation from the start of the drag to the result, in world and local space
+fn rotation_deltas(result: &GizmoResult) -> (Quat, Quat) {
+    let start = Quat::from_mat4(&rotated_model());
+    let rotation = Quat::from(result.rotation);
+    (rotation * start.inverse(), start.inverse() * rotation)
+}
+
+fn assert_axis(rotation: Quat, expected: Vec3) {
+    let (axis, angle) = rotation.to_axis_angle();
+    assert!(angle > 0.1, "rotation angle {angle} is too small");
+    assert!(
+        axis.cross(expected).length() < 1e-4,
+        "rotation axis {axis} is not parallel to {expected}"
+    );
+}
+
+#[test]
+fn local_ring_rotates_around_rotated_axis() {
+    let mut harness = Harness::front();
+    let center = harness.center();
+    let ring_point = |angle: f32| center + Vec2::angled(angle.to_radians()) * GIZMO_SIZE;
+
+    let result = harness
+        .drag(ring_point(45.0), ring_point(135.0), || {
+            // The ring is at the edge of the arcball
+            rotation_gizmo(GizmoOrientation::Local)
+                .pick_priority(|handle| i32::from(handle.transform_kind.is_some()))
+        })
+        .expect("dragging the ring should produce a result");
+
+    assert_eq!(handle_name(&result), Some("Rotate X".to_owned()));
+
+    // Rotating around the local X axis is the same as rotating around
+    // the rotated X axis in world space, which points towards the camera.
+    let (world_delta, local_delta) = rotation_deltas(&result);
+    assert_axis(world_delta, Vec3::Z);
+    assert_axis(local_delta, Vec3::X);
+}
+
+#[test]
+fn arcball_reports_the_mode_of_the_gizmo() {
+    let center = Harness::front().center();
+    let from = center + Vec2::new(-25.0, -25.0);
+    let to = center + Vec2::new(25.0, -25.0);
+
+    for mode in [GizmoMode::Rotate, GizmoMode::Arcball] {
+        let result = Harness::front()
+            .drag(from, to, || Gizmo::new("gizmo").mode(mode))
+            .expect("dragging the arcball should produce a result");
+        assert_eq!(handle_name(&result), Some("Arcball".to_owned()));
+        assert_eq!(result.mode, mode);
+    }
+}
+
+#[test]
+fn arcball_composes_rotation_like_rings() {
+    let center = Harness::front().center();
+    let from = center + Vec2::new(-25.0, -25.0);
+    let to = center + Vec2::new(25.0, -25.0);
+
+    let results = [GizmoOrientation::Global, GizmoOrientation::Local].map(|orientation| {
+        let result = Harness::front()
+            .drag(from, to, || rotation_gizmo(orientation))
+            .expect("dragging the arcball should produce a result");
+
+        assert_eq!(handle_name(&result), Some("Arcball".to_owned()));
+        result
+    });
+
+    // The arcball rotates around an axis in view space, which is applied in world space
+    // like the rotation around the world space normal of the rings. The result is the
+    // same in both orientations and the object follows the pointer regardless of its
+    // own rotation.
+    let [global, local] = results.map(|result| rotation_deltas(&result).0);
+    assert!(global.abs_diff_eq(local, 1e-5));
+
+    let (axis, _) = global.to_axis_angle();
+    assert!(
+        axis.dot(Vec3::Y).abs() > 0.9,
+        "rotation axis {axis} is not close to the Y axis"
+    );
+}
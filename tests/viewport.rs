@@ -0,0 +1,182 @@
+use egui::{Event, Pos2, Rect, Vec2};
+use egui_gizmo::{Gizmo, GizmoMode};
+use glam::{Mat4, Vec3};
+
+use common::{handle_name, pointer_button, Harness, GIZMO_SIZE, SCREEN_SIZE};
+
+mod common;
+
+fn gizmo() -> Gizmo {
+    Gizmo::new("gizmo").mode(GizmoMode::Translate)
+}
+
+#[test]
+fn press_outside_viewport_does_not_activate_gizmo() {
+    // The gizmo viewport covers the right half of the screen,
+    // like a 3d view next to a side panel.
+    let viewport = Rect::from_min_max(Pos2::new(SCREEN_SIZE.x / 2.0, 0.0), SCREEN_SIZE.to_pos2());
+    let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+    let mut harness = Harness::with_viewport(view, viewport);
+
+    let outside = Pos2::new(100.0, 300.0);
+    let gizmo_center = harness.center();
+
+    let frames = [
+        vec![Event::PointerMoved(outside)],
+        vec![pointer_button(outside, true)],
+        vec![Event::PointerMoved(gizmo_center - Vec2::new(20.0, 0.0))],
+        vec![Event::PointerMoved(gizmo_center)],
+        vec![Event::PointerMoved(gizmo_center + Vec2::new(0.0, 20.0))],
+    ];
+
+    for (i, events) in frames.into_iter().enumerate() {
+        let interaction = harness.frame(events, gizmo());
+        assert!(interaction.result.is_none(), "frame {i} produced a result");
+        assert!(
+            interaction.hovered.is_none(),
+            "frame {i} hovered a subgizmo"
+        );
+    }
+
+    // The gizmo is hovered normally once the button is released
+    let pos = gizmo_center + Vec2::new(0.0, 20.0);
+    let _ = harness.frame(vec![pointer_button(pos, false)], gizmo());
+    let interaction = harness.frame(vec![Event::PointerMoved(gizmo_center)], gizmo());
+    assert!(interaction.hovered.is_some());
+    assert!(interaction.result.is_none());
+}
+
+/// Viewport of an embedded 3d view, offset by a toolbox on the left and a toolbar on top
+fn embedded_viewport() -> Rect {
+    Rect::from_min_size(Pos2::new(320.0, 64.0), Vec2::new(1280.0, 720.0))
+}
+
+#[test]
+fn offset_viewport_projects_gizmo_to_its_center() {
+    let viewport = embedded_viewport();
+    let view = Mat4::look_at_rh(Vec3::new(3.0, 4.0, 10.0), Vec3::ZERO, Vec3::Y);
+    let mut harness = Harness::with_viewport(view, viewport);
+
+    let interaction = harness.frame(vec![Event::PointerMoved(viewport.center())], gizmo());
+    let debug_info = interaction
+        .debug_info
+        .expect("debug info should be reported");
+    let gizmo_pos = debug_info
+        .gizmo_screen_pos
+        .expect("the gizmo should be in front of the camera");
+
+    assert_eq!(debug_info.viewport, viewport);
+    assert!(
+        gizmo_pos.distance(viewport.center()) < 1e-3,
+        "gizmo at {gizmo_pos:?} is not at the viewport center {:?}",
+        viewport.center()
+    );
+    assert_eq!(
+        interaction.hovered.map(|handle| handle.to_string()),
+        Some("Translate view plane".to_owned())
+    );
+}
+
+#[test]
+fn offset_viewport_picks_handles_under_pointer() {
+    let viewport = embedded_viewport();
+    let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+    let mut harness = Harness::with_viewport(view, viewport);
+    let center = viewport.center();
+
+    // The handles are found at the same offsets from the gizmo as in a viewport at the
+    // origin, and not at offsets shifted by the viewport position.
+    for (offset, expected) in [
+        (Vec2::new(GIZMO_SIZE * 0.6, 0.0), Some("Translate X")),
+        (Vec2::new(0.0, -GIZMO_SIZE * 0.6), Some("Translate Y")),
+        (
+            Vec2::new(GIZMO_SIZE * 0.6, 0.0) - viewport.min.to_vec2(),
+            None,
+        ),
+    ] {
+        let interaction = harness.frame(vec![Event::PointerMoved(center + offset)], gizmo());
+        assert_eq!(
+            interaction
+                .hovered
+                .map(|handle| handle.to_string())
+                .as_deref(),
+            expected,
+            "offset {offset:?}"
+        );
+    }
+
+    // Dragging the X arrow moves the gizmo along X only
+    let from = center + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let result = harness
+        .drag(from, from + Vec2::new(40.0, 0.0), gizmo)
+        .expect("dragging the X arrow should produce a result");
+    assert_eq!(handle_name(&result), Some("Translate X".to_owned()));
+    let translation = Vec3::from(result.translation);
+    assert!(translation.x > 0.0 && translation.y.abs() < 1e-4 && translation.z.abs() < 1e-4);
+}
+
+/// Screen position of the world origin, projected independently of the gizmo
+fn projected_origin(view: Mat4, projection: Mat4, viewport: Rect) -> Pos2 {
+    let ndc = (projection * view).project_point3(Vec3::ZERO);
+    Pos2::new(
+        viewport.center().x + ndc.x * viewport.width() / 2.0,
+        viewport.center().y - ndc.y * viewport.height() / 2.0,
+    )
+}
+
+/// Asserts that the gizmo is shown at the projected origin, keeps its size on the screen
+/// and is picked under the pointer
+fn assert_off_center_projection(projection: Mat4) {
+    let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 10.0), Vec3::ZERO, Vec3::Y);
+    let mut harness = Harness::with_projection(view, projection);
+    let origin = projected_origin(view, projection, harness.viewport());
+    assert!(origin.distance(harness.center()) > 50.0);
+
+    let pos = origin + Vec2::new(GIZMO_SIZE * 0.6, 0.0);
+    let interaction = harness.frame(vec![Event::PointerMoved(pos)], gizmo());
+    let debug_info = interaction
+        .debug_info
+        .expect("the debug info should be reported");
+    let screen_pos = debug_info
+        .gizmo_screen_pos
+        .expect("the gizmo should be shown");
+    assert!(
+        screen_pos.distance(origin) < 0.5,
+        "the gizmo is at {screen_pos:?} instead of {origin:?}"
+    );
+    let size = interaction.bounding_rect.size();
+    let expected = Harness::front().frame(vec![], gizmo()).bounding_rect.size();
+    assert!(
+        (size - expected).length() < expected.length() * 0.1,
+        "the gizmo is {size:?} instead of {expected:?} points"
+    );
+    assert_eq!(
+        interaction.hovered.map(|handle| handle.to_string()),
+        Some("Translate X".to_owned())
+    );
+
+    let from = origin + Vec2::new(0.0, -GIZMO_SIZE * 0.6);
+    let result = harness
+        .drag(from, from + Vec2::new(0.0, -40.0), gizmo)
+        .expect("dragging the Y handle should produce a result");
+    assert_eq!(handle_name(&result), Some("Translate Y".to_owned()));
+}
+
+#[test]
+fn asymmetric_orthographic_projection_is_supported() {
+    assert_off_center_projection(Mat4::orthographic_rh(-2.0, 6.0, -1.0, 5.0, 0.1, 100.0));
+}
+
+#[test]
+fn sheared_perspective_projection_is_supported() {
+    let mut projection = Mat4::perspective_rh(
+        45_f32.to_radians(),
+        SCREEN_SIZE.x / SCREEN_SIZE.y,
+        0.1,
+        100.0,
+    );
+    // Off-center terms shift the frustum, as for the eyes of a VR headset
+    projection.z_axis.x = 0.3;
+    projection.z_axis.y = -0.2;
+    assert_off_center_projection(projection);
+}